@@ -0,0 +1,97 @@
+use crate::chessboard::{EMPTY, Players};
+
+/*
+ * One bit per square, bit 0 is a1 and bit 63 is h8, the same order as ChessBoard::board.
+ */
+pub type Bitboard = u64;
+
+/*
+ * The pieces of a position as bitboards, kept next to the mailbox board so both piece lookups per square and
+ * set operations on whole piece types are cheap.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Bitboards {
+    pub pieces: [[Bitboard; 6]; 2], /* Indexed by colour_index, then by piece type WPAWN - 1 up to WKING - 1 */
+    pub colours: [Bitboard; 2],
+    pub occupied: Bitboard,
+}
+
+impl Bitboards {
+    pub fn from_board(board: &[i8; 64]) -> Self {
+        let mut bitboards = Self::default();
+
+        for (position, piece) in board.iter().enumerate() {
+            if *piece != EMPTY {
+                bitboards.toggle_piece(position, *piece);
+            }
+        }
+
+        bitboards
+    }
+
+    /*
+     * Add the piece to the square, or remove it if it is already there.
+     */
+    pub fn toggle_piece(&mut self, position: usize, piece: i8) {
+        debug_assert!(piece != EMPTY);
+
+        let bit = square_bit(position);
+        let colour = colour_index(piece_colour(piece));
+
+        self.pieces[colour][(piece.unsigned_abs() - 1) as usize] ^= bit;
+        self.colours[colour] ^= bit;
+        self.occupied ^= bit;
+    }
+
+    /* piece_type is the white piece constant, e.g. WKNIGHT for the knights of either side. */
+    pub fn pieces(&self, side: Players, piece_type: i8) -> Bitboard {
+        self.pieces[colour_index(side)][(piece_type - 1) as usize]
+    }
+
+    pub fn colour(&self, side: Players) -> Bitboard {
+        self.colours[colour_index(side)]
+    }
+}
+
+pub fn colour_index(side: Players) -> usize {
+    match side {
+        Players::White => 0,
+        Players::Black => 1,
+    }
+}
+
+pub fn piece_colour(piece: i8) -> Players {
+    if piece.is_positive() {
+        Players::White
+    } else {
+        Players::Black
+    }
+}
+
+pub const fn square_bit(position: usize) -> Bitboard {
+    1 << position
+}
+
+/*
+ * Iterate over the squares of the set bits, from a1 up to h8.
+ */
+pub fn squares(bitboard: Bitboard) -> Squares {
+    Squares(bitboard)
+}
+
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let position = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+
+        Some(position)
+    }
+}
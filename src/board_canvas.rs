@@ -0,0 +1,254 @@
+use crate::bitboard::{Bitboard, square_bit};
+use crate::chess_game::{Message, MoveTarget, SquareHighlight, SvgPieces, get_corrected_index};
+use crate::chessboard::{EMPTY, Players, should_be_light_square};
+use iced::mouse;
+use iced::widget::canvas::{self, Action, Event, Frame, Geometry, Path, Stroke};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, Vector};
+
+const LAST_MOVE_LIGHT: Color = Color::from_rgb8(205, 210, 106);
+const LAST_MOVE_DARK: Color = Color::from_rgb8(170, 162, 58);
+const CHECK_COLOUR: Color = Color::from_rgb8(230, 70, 60);
+const MOVE_HINT_COLOUR: Color = Color::from_rgba8(20, 85, 30, 0.5);
+
+/* How far, as a part of a square, the mouse has to move with the button held before a press becomes a drag. */
+const DRAG_THRESHOLD: f32 = 0.1;
+
+/*
+ * The board drawn as one canvas that scales with its size. Pieces are moved by clicking the piece and then its
+ * target square, or by dragging the piece onto the target. A drop that is not a legal move leaves the piece where
+ * it was, as the position does not change.
+ */
+pub struct BoardCanvas<'a> {
+    pub board: &'a [i8; 64],
+    pub highlights: [SquareHighlight; 64],
+    pub movable: Bitboard, // The squares of the pieces that can be picked up.
+    pub perspective: Players,
+    pub pieces: &'a SvgPieces,
+    pub can_update: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct BoardState {
+    drag: Option<Drag>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    from: usize,
+    start: Point,
+    moved: bool, // Whether the mouse moved far enough to drag, otherwise the press is a click.
+    was_selected: bool, // Releasing a piece that was selected before without moving it unselects it.
+}
+
+/*
+ * The square under a point relative to the top left corner of a board of the given size, None outside the board.
+ */
+pub fn square_at(point: Point, size: Size, perspective: Players) -> Option<usize> {
+    let square_size = size.width.min(size.height) / 8.;
+
+    if point.x < 0. || point.y < 0. || square_size <= 0. {
+        return None;
+    }
+
+    let column = (point.x / square_size) as usize;
+    let row = (point.y / square_size) as usize;
+
+    (column < 8 && row < 8).then(|| get_corrected_index(row * 8 + column, perspective))
+}
+
+impl BoardCanvas<'_> {
+    fn square_colour(&self, theme: &Theme, position: usize, hovered: bool) -> Color {
+        let highlight = self.highlights[position];
+        let palette = theme.extended_palette();
+        let light = theme.palette().text;
+        let is_light = should_be_light_square(position);
+
+        if highlight.checked_king {
+            CHECK_COLOUR
+        } else if highlight.selected {
+            if is_light {
+                light.scale_alpha(0.5)
+            } else {
+                palette.primary.base.color
+            }
+        } else if highlight.last_move {
+            if is_light {
+                LAST_MOVE_LIGHT
+            } else {
+                LAST_MOVE_DARK
+            }
+        } else if hovered {
+            if is_light {
+                light.scale_alpha(0.7)
+            } else {
+                palette.primary.weak.color
+            }
+        } else if is_light {
+            light
+        } else {
+            palette.primary.strong.color
+        }
+    }
+}
+
+impl canvas::Program<Message> for BoardCanvas<'_> {
+    type State = BoardState;
+
+    fn update(
+        &self,
+        state: &mut BoardState,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<Action<Message>> {
+        if !self.can_update {
+            state.drag = None;
+            return None;
+        }
+
+        let square_under_cursor = cursor
+            .position_in(bounds)
+            .and_then(|point| square_at(point, bounds.size(), self.perspective));
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let square = square_under_cursor?;
+                let was_selected = self.highlights[square].selected;
+
+                if self.movable & square_bit(square) != 0 {
+                    state.drag = Some(Drag {
+                        from: square,
+                        start: cursor.position()?,
+                        moved: false,
+                        was_selected,
+                    });
+                }
+
+                // A press on the selected piece may start a drag, only a release unselects it.
+                Some(if was_selected {
+                    Action::capture()
+                } else {
+                    Action::publish(Message::ClickedSquare(square)).and_capture()
+                })
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                let drag = state.drag.as_mut()?;
+                let square_size = bounds.width.min(bounds.height) / 8.;
+
+                if !drag.moved && drag.start.distance(*position) > square_size * DRAG_THRESHOLD {
+                    drag.moved = true;
+                }
+
+                drag.moved.then(Action::request_redraw)
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let drag = state.drag.take()?;
+
+                let message = if drag.moved {
+                    square_under_cursor
+                        .filter(|square| *square != drag.from)
+                        .map(|square| Message::DroppedPiece(drag.from, square))
+                } else if drag.was_selected {
+                    Some(Message::ClickedSquare(drag.from))
+                } else {
+                    None
+                };
+
+                Some(message.map_or_else(Action::request_redraw, Action::publish))
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &BoardState,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        let square_size = bounds.width.min(bounds.height) / 8.;
+        let square = Size::new(square_size, square_size);
+        let ring_width = square_size / 14.;
+
+        let cursor_position = cursor.position_in(bounds);
+        let hovered = cursor_position
+            .and_then(|point| square_at(point, bounds.size(), self.perspective))
+            .filter(|_| self.can_update);
+        let dragged = state.drag.filter(|drag| drag.moved).map(|drag| drag.from);
+
+        for index in 0..64 {
+            let position = get_corrected_index(index, self.perspective);
+            let piece = self.board[position];
+            let top_left = Point::new(
+                (index % 8) as f32 * square_size,
+                (index / 8) as f32 * square_size,
+            );
+            let center = top_left + Vector::new(square_size / 2., square_size / 2.);
+
+            frame.fill_rectangle(
+                top_left,
+                square,
+                self.square_colour(theme, position, hovered == Some(position)),
+            );
+
+            // Captures get a ring around the piece, moves to an empty square a dot in its middle.
+            match self.highlights[position].target {
+                Some(MoveTarget::Capture) => frame.stroke(
+                    &Path::circle(center, (square_size - ring_width) / 2.),
+                    Stroke::default()
+                        .with_color(MOVE_HINT_COLOUR)
+                        .with_width(ring_width),
+                ),
+                Some(MoveTarget::Quiet) if piece == EMPTY => {
+                    frame.fill(&Path::circle(center, square_size / 6.), MOVE_HINT_COLOUR)
+                }
+                _ => {}
+            }
+
+            if piece != EMPTY && dragged != Some(position) {
+                frame.draw_svg(Rectangle::new(top_left, square), &self.pieces.handle(piece));
+            }
+        }
+
+        // The dragged piece floats above the board, centred on the cursor.
+        if let Some(from) = dragged
+            && let Some(point) = cursor_position
+        {
+            let top_left = point - Vector::new(square_size / 2., square_size / 2.);
+
+            frame.draw_svg(
+                Rectangle::new(top_left, square),
+                &self.pieces.handle(self.board[from]),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &BoardState,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.drag.is_some_and(|drag| drag.moved) {
+            return mouse::Interaction::Grabbing;
+        }
+
+        let hovered = cursor
+            .position_in(bounds)
+            .and_then(|point| square_at(point, bounds.size(), self.perspective));
+
+        match hovered {
+            Some(square) if self.can_update && self.movable & square_bit(square) != 0 => {
+                mouse::Interaction::Grab
+            }
+            Some(_) if self.can_update => mouse::Interaction::Pointer,
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::chessboard::{ChessBoard, EMPTY, WBISHOP, WKING, WKNIGHT, WQUEEN, WROOK, piece_to_char};
+use crate::fen::Fen;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const CHESS960_POSITIONS: usize = 960;
+/* The number of the standard start position in the Scharnagl numbering. */
+pub const STANDARD_POSITION: usize = 518;
+
+/* Where the two knights go among the five squares left after placing the bishops and the queen. */
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/*
+ * The white pieces on the first rank of start position number 0-959 in the Scharnagl numbering. The bishops go on
+ * opposite colours, the queen and knights on the free squares, and the king between the rooks on the last three.
+ */
+pub fn chess960_back_rank(number: usize) -> [i8; 8] {
+    debug_assert!(number < CHESS960_POSITIONS);
+
+    let mut back_rank = [EMPTY; 8];
+
+    let mut number = number;
+    back_rank[2 * (number % 4) + 1] = WBISHOP;
+    number /= 4;
+    back_rank[2 * (number % 4)] = WBISHOP;
+    number /= 4;
+
+    let nth_empty_file = |back_rank: &[i8; 8], n: usize| {
+        (0..8)
+            .filter(|file| back_rank[*file] == EMPTY)
+            .nth(n)
+            .expect("The back rank has enough empty squares")
+    };
+
+    let queen = nth_empty_file(&back_rank, number % 6);
+    back_rank[queen] = WQUEEN;
+    number /= 6;
+
+    // Find both squares before placing a knight, the second index counts the first knight's square as empty.
+    let (first, second) = KNIGHT_PLACEMENTS[number];
+    let knights = [
+        nth_empty_file(&back_rank, first),
+        nth_empty_file(&back_rank, second),
+    ];
+    knights.iter().for_each(|file| back_rank[*file] = WKNIGHT);
+
+    for piece in [WROOK, WKING, WROOK] {
+        let file = nth_empty_file(&back_rank, 0);
+        back_rank[file] = piece;
+    }
+
+    back_rank
+}
+
+/*
+ * The board of start position number 0-959, with all castling rights.
+ */
+pub fn chess960_position(number: usize) -> ChessBoard {
+    let white: String = chess960_back_rank(number)
+        .iter()
+        .map(|piece| piece_to_char(*piece))
+        .collect();
+    let black = white.to_lowercase();
+
+    ChessBoard::set_fen_position(&format!(
+        "{black}/pppppppp/8/8/8/8/PPPPPPPP/{white} w KQkq - 0 1"
+    ))
+    .expect("Chess960 start positions should be valid FEN")
+}
+
+/*
+ * A start position picked from the clock, random enough to start a game with.
+ */
+pub fn random_chess960_position() -> ChessBoard {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.subsec_nanos());
+
+    chess960_position(nanos as usize % CHESS960_POSITIONS)
+}
@@ -1,22 +1,141 @@
+use crate::bitboard::{Bitboard, square_bit};
+use crate::board_canvas::BoardCanvas;
+use crate::chess960::random_chess960_position;
 use crate::chessboard::{
-    BBISHOP, BKING, BKNIGHT, BPAWN, BQUEEN, BROOK, ChessBoard, EMPTY, Players, WBISHOP, WKING,
-    WKNIGHT, WPAWN, WQUEEN, WROOK,
+    BBISHOP, BKING, BKNIGHT, BPAWN, BQUEEN, BROOK, ChessBoard, Players, WBISHOP, WKING, WKNIGHT,
+    WPAWN, WQUEEN, WROOK,
 };
+use crate::clock::{Clock, IncrementKind, TIME_CONTROLS, TimeControl, format_time};
 use crate::engine::ChessEngine;
-use crate::moves::MoveType;
-use iced::widget::{Row, button, column, container, row, svg, text};
+use crate::fen::Fen;
+use crate::moves::{LegalMove, MoveType};
+use crate::outcome::{GameOutcome, timeout_outcome};
+use crate::pgn::{PgnGame, PgnMove, PgnResult, PgnVariation};
+use crate::search::SearchLimits;
+use crate::transposition::{DEFAULT_HASH_MB, TranspositionTable};
+use iced::futures::channel::oneshot;
+use iced::keyboard::{self, key};
+use iced::widget::{
+    button, canvas, center, column, container, opaque, row, scrollable, stack, svg, text,
+    text_input,
+};
 use iced::{self, ContentFit, Event, Length, Padding};
 use iced::{Element, Fill, Task};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/* How often the clock of the side to move is redrawn and checked for running out of time. */
+const CLOCK_TICK: Duration = Duration::from_millis(100);
+/* The computer plans to use no more than this part of its remaining time for a move. */
+const ENGINE_TIME_SHARE: u32 = 30;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Start,
     Quit,
     Reset,
+    NewChess960Game,
     ClickedSquare(usize),
+    DroppedPiece(usize, usize), // A piece was dragged from the first square and dropped on the second.
+    Navigate(Navigation),
+    PlayFromHere,
     Event(Event),
     SwitchPerspective,
     PromotionTriggered(i8),
+    ClaimDraw,
+    Undo,
+    Redo,
+    SelectColour(Players),
+    SelectOpponent(Option<Strength>), // None means a human opponent.
+    SelectTimeControl(Option<TimeControl>), // None means a game without clocks.
+    CustomMinutesChanged(String),
+    CustomIncrementChanged(String),
+    SelectIncrementKind(IncrementKind),
+    Tick(Instant),
+    Resign,
+    AgreeDraw,
+    Rematch,
+    ShowStartScreen,
+    SavePgn,
+    CopyFen,
+    EngineMoved(u64, Option<LegalMove>), // The id of the search and the move it found.
+}
+
+/*
+ * Moves through the positions of the game. Earlier positions can be looked at, but not played from until the
+ * game continues from there with Message::PlayFromHere.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Navigation {
+    First,
+    Previous,
+    Next,
+    Last,
+    Ply(usize), // The position after this many moves.
+}
+
+/*
+ * How hard the computer plays, either a fixed search depth or a fixed time per move in milliseconds.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strength {
+    Depth(u32),
+    MoveTime(u64),
+}
+
+pub const STRENGTHS: [Strength; 5] = [
+    Strength::Depth(1),
+    Strength::Depth(2),
+    Strength::Depth(3),
+    Strength::MoveTime(1000),
+    Strength::MoveTime(3000),
+];
+
+impl Strength {
+    pub fn search_limits(&self, stop: Arc<AtomicBool>) -> SearchLimits {
+        let (depth, time) = match *self {
+            Strength::Depth(depth) => (Some(depth), None),
+            Strength::MoveTime(millis) => (None, Some(Duration::from_millis(millis))),
+        };
+
+        SearchLimits {
+            depth,
+            time,
+            stop: Some(stop),
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strength::Depth(depth) => write!(f, "Depth {depth}"),
+            Strength::MoveTime(millis) => write!(f, "{} s/move", *millis as f64 / 1000.),
+        }
+    }
+}
+
+/*
+ * How a square is marked on the board on top of its own colour.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SquareHighlight {
+    pub selected: bool,
+    pub last_move: bool,    // The from or to square of the move played last.
+    pub checked_king: bool, // The king of the side to move stands here in check.
+    pub target: Option<MoveTarget>, // The selected piece can move here.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveTarget {
+    Quiet,
+    Capture,
 }
 
 pub struct SvgPieces {
@@ -67,7 +186,11 @@ impl Default for SvgPieces {
 
 impl SvgPieces {
     pub fn to_iced_svg(&self, square: i8) -> iced::widget::Svg<'_> {
-        let handle = svg::Handle::from_memory(match square {
+        svg(self.handle(square))
+    }
+
+    pub fn handle(&self, square: i8) -> svg::Handle {
+        svg::Handle::from_memory(match square {
             WKING => self.white_king,
             BKING => self.black_king,
 
@@ -87,97 +210,131 @@ impl SvgPieces {
             BPAWN => self.black_pawn,
 
             _ => unreachable!("Should always give valid peace to function!"),
-        });
-
-        svg(handle)
+        })
     }
 }
 
+/*
+ * A move of the game with its standard algebraic notation, which depends on the position it was played in.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayedMove {
+    pub legal_move: LegalMove,
+    pub san: String,
+}
+
+/*
+ * A line that was given up by playing another move from an earlier position. It branches off at position ply of
+ * the line it was played from, lines given up inside it are kept with it.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variation {
+    pub ply: usize,
+    pub moves: Vec<PlayedMove>,
+    pub variations: Vec<Variation>,
+}
+
 pub struct ChessGame {
-    game: Option<ChessBoard>,
+    positions: Vec<ChessBoard>, // The starting position and the position after every move, empty before a game.
+    moves: Vec<PlayedMove>,     // moves[i] leads from positions[i] to positions[i + 1].
+    viewed_ply: usize, // The index of the position shown, the last one unless looking back.
     perspective: Players,
     selected_square: Option<usize>,
     piece_sprite: SvgPieces,
     window_size: Option<iced::Size>,
     promotion_choice: Option<(Players, usize)>, // Current player color and the target position of the pawn.
+    undone_moves: Vec<LegalMove>, // The last undone move comes last, kept as a variation when another move is played.
+    variations: Vec<Variation>, // The lines given up for the moves of the game, in the order they branch off.
+    outcome: Option<GameOutcome>,
+    claimable_draw: Option<GameOutcome>,
+    player_colour: Players,
+    computer: Option<Strength>, // None when two humans play on one board.
+    time_control: Option<TimeControl>,
+    custom_minutes: String, // The custom time control as typed on the start screen.
+    custom_increment: String,
+    custom_kind: IncrementKind,
+    clock: Option<Clock>,          // Only in games with a time control.
+    clocks: Vec<Clock>, // The clock as each position was reached, stopped, in games with a time control.
+    undone_clocks: Vec<Clock>, // The clocks of the undone moves, in the order of undone_moves.
+    export_status: Option<String>, // What became of the last save or copy of a finished game.
+    engine_thinking: bool,
+    search_id: u64, // Results of searches from an earlier position or game are ignored.
+    search_stop: Arc<AtomicBool>,
+    transposition_table: Arc<TranspositionTable>, // Kept from move to move, shared with the search thread.
 }
 
 impl Default for ChessGame {
     fn default() -> Self {
         Self {
-            game: None,
+            positions: Vec::new(),
+            moves: Vec::new(),
+            viewed_ply: 0,
             perspective: Players::White,
             selected_square: None,
             piece_sprite: SvgPieces::default(),
             window_size: None,
             promotion_choice: None,
+            undone_moves: Vec::new(),
+            variations: Vec::new(),
+            outcome: None,
+            claimable_draw: None,
+            player_colour: Players::White,
+            computer: None,
+            time_control: None,
+            custom_minutes: String::from("10"),
+            custom_increment: String::from("5"),
+            custom_kind: IncrementKind::Fischer,
+            clock: None,
+            clocks: Vec::new(),
+            undone_clocks: Vec::new(),
+            export_status: None,
+            engine_thinking: false,
+            search_id: 0,
+            search_stop: Arc::new(AtomicBool::new(false)),
+            transposition_table: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
         }
     }
 }
 
 impl ChessGame {
     pub fn subscription(&self) -> iced::Subscription<Message> {
-        iced::event::listen().map(Message::Event)
+        let events = iced::event::listen().map(Message::Event);
+
+        if self
+            .clock
+            .as_ref()
+            .is_some_and(|clock| clock.running().is_some())
+        {
+            iced::Subscription::batch([events, iced::time::every(CLOCK_TICK).map(Message::Tick)])
+        } else {
+            events
+        }
     }
 
     pub fn update(&mut self, msg: Message) -> Task<Message> {
         match msg {
-            Message::Start | Message::Reset => {
-                self.game = Some(ChessBoard::default());
+            Message::Start | Message::Reset => self.start_game(ChessBoard::default()),
+            Message::NewChess960Game => self.start_game(random_chess960_position()),
+            Message::ClickedSquare(_) | Message::DroppedPiece(..) if !self.can_move() => {
                 Task::none()
             }
-            Message::ClickedSquare(square) => match self.selected_square {
-                None => {
-                    self.selected_square = Some(square);
-
-                    Task::none()
+            Message::ClickedSquare(square) => self.click_square(square),
+            Message::DroppedPiece(from, to) => self.drop_piece(from, to),
+            Message::Navigate(navigation) => {
+                self.navigate(navigation);
+                Task::none()
+            }
+            Message::PlayFromHere => {
+                if !self.can_take_back() {
+                    return Task::none();
                 }
-                Some(from) => match self.game.clone() {
-                    None => {
-                        unreachable!("Should only be able to move when initialized!")
-                    }
-                    Some(mut game) => {
-                        if from == square {
-                            self.selected_square = None;
-                            return Task::none();
-                        }
-                        let legal_moves = game.legal_moves();
-                        for legal_move in legal_moves.iter() {
-                            match legal_move.move_type {
-                                MoveType::PawnMove {
-                                    promotion_move: Some(_prom_move),
-                                } => {
-                                    if legal_move.from == from && legal_move.to == square {
-                                        self.promotion_choice = Some((
-                                            self.game
-                                                .as_ref()
-                                                .expect("Game should exist!")
-                                                .side_to_move,
-                                            square,
-                                        ));
-                                        return Task::none();
-                                    }
-                                }
-
-                                _ => {
-                                    if legal_move.from == from && legal_move.to == square {
-                                        game.make_move(*legal_move);
-                                        self.game = Some(game);
-                                        self.selected_square = None;
-                                        return Task::none();
-                                    }
-                                }
-                            }
-                        }
 
-                        if legal_moves.is_empty() {
-                            todo!("No legal moves, need a way to check for winner/draw");
-                        }
+                self.cancel_search();
 
-                        Task::none()
-                    }
-                },
-            },
+                while self.viewed_ply < self.moves.len() && self.undo_move() {}
+
+                self.after_history_change()
+            }
             Message::Quit => iced::exit(),
 
             Message::Event(event) => match event {
@@ -185,6 +342,24 @@ impl ChessGame {
                     self.window_size = Some(size);
                     iced::Task::none()
                 }
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: keyboard::Key::Named(key),
+                    ..
+                }) => {
+                    let navigation = match key {
+                        key::Named::Home => Some(Navigation::First),
+                        key::Named::ArrowLeft => Some(Navigation::Previous),
+                        key::Named::ArrowRight => Some(Navigation::Next),
+                        key::Named::End => Some(Navigation::Last),
+                        _ => None,
+                    };
+
+                    if let Some(navigation) = navigation {
+                        self.navigate(navigation);
+                    }
+
+                    iced::Task::none()
+                }
                 _ => iced::Task::none(),
             },
             Message::SwitchPerspective => {
@@ -197,41 +372,770 @@ impl ChessGame {
                 iced::Task::none()
             }
             Message::PromotionTriggered(desired_piece) => {
-                let mut game = self.game.clone().unwrap();
+                // Without a game or a pawn waiting to promote, the message is stale.
+                let (Some(game), Some(wanted_move)) =
+                    (self.current_position(), self.promotion_choice)
+                else {
+                    return Task::none();
+                };
 
                 let legal_moves = game.legal_moves();
 
-                let wanted_move = self.promotion_choice.expect("MUST EXIST");
-
                 let desired_move = legal_moves
                     .iter()
-                    .find(|legal_move| matches!(legal_move.move_type, MoveType::PawnMove { promotion_move: Some(p)} if p == desired_piece) && legal_move.to == wanted_move.1);
+                    .find(|legal_move| matches!(legal_move.move_type, MoveType::PawnMove { promotion_move: Some(p)} if p == desired_piece) && legal_move.to == wanted_move.1 && Some(legal_move.from) == self.selected_square);
 
-                if desired_move.is_none() {
-                    eprintln!("Desired move could not be found");
-                    return iced::exit();
-                }
+                // A choice that no longer fits the position is ignored.
+                let Some(desired_move) = desired_move else {
+                    return Task::none();
+                };
+
+                self.play_move(*desired_move);
 
-                let desired_move = desired_move.expect("checked above");
+                self.promotion_choice = None;
 
-                game.make_move(*desired_move);
+                self.request_engine_move()
+            }
+            Message::ClaimDraw => {
+                if let Some(draw) = self.claimable_draw {
+                    self.end_game(draw, Instant::now());
+                }
+                iced::Task::none()
+            }
+            Message::Resign => {
+                // Against the computer the player resigns, otherwise the side to move.
+                let loser = match self.computer {
+                    Some(_) => Some(self.player_colour),
+                    None => self.current_position().map(|game| game.side_to_move),
+                };
 
-                self.game = Some(game);
+                if let Some(loser) = loser {
+                    let winner = match loser {
+                        Players::White => Players::Black,
+                        Players::Black => Players::White,
+                    };
+                    self.end_game(GameOutcome::Resignation { winner }, Instant::now());
+                }
+                iced::Task::none()
+            }
+            Message::AgreeDraw => {
+                self.end_game(GameOutcome::DrawAgreed, Instant::now());
+                iced::Task::none()
+            }
+            Message::Rematch => {
+                let Some(start) = self.positions.first().cloned() else {
+                    return iced::Task::none();
+                };
 
-                self.promotion_choice = None;
+                // The colours swap: the player takes the other side against the computer, two players swap seats.
+                self.player_colour = match self.player_colour {
+                    Players::White => Players::Black,
+                    Players::Black => Players::White,
+                };
+                self.perspective = match self.perspective {
+                    Players::White => Players::Black,
+                    Players::Black => Players::White,
+                };
+
+                self.start_game(start)
+            }
+            Message::ShowStartScreen => {
+                self.cancel_search();
+                self.positions.clear();
+                self.moves.clear();
+                self.viewed_ply = 0;
+                self.undone_moves.clear();
+                self.variations.clear();
+                self.outcome = None;
+                self.claimable_draw = None;
                 self.selected_square = None;
+                self.promotion_choice = None;
+                self.clock = None;
+                self.clocks.clear();
+                self.undone_clocks.clear();
+                self.export_status = None;
+                iced::Task::none()
+            }
+            Message::SavePgn => {
+                self.export_status = Some(match self.save_pgn() {
+                    Ok(path) => format!("Saved to {}", path.display()),
+                    Err(error) => format!("Could not save the game: {error}"),
+                });
+                iced::Task::none()
+            }
+            Message::CopyFen => {
+                let Some(game) = self.current_position() else {
+                    return iced::Task::none();
+                };
 
+                let fen = game.to_fen();
+                self.export_status = Some(String::from("Copied the final position"));
+                iced::clipboard::write(fen)
+            }
+            Message::Undo => {
+                if !self.can_take_back() {
+                    return Task::none();
+                }
+
+                self.cancel_search();
+
+                // Against the computer, take back its reply as well so it is the player's turn again.
+                while self.undo_move() && self.is_engine_turn() {}
+
+                self.after_history_change()
+            }
+            Message::Redo => {
+                if !self.can_take_back() {
+                    return Task::none();
+                }
+
+                self.cancel_search();
+
+                while self.redo_move() && self.is_engine_turn() {}
+
+                self.after_history_change()
+            }
+            Message::SelectColour(colour) => {
+                self.player_colour = colour;
                 iced::Task::none()
             }
+            Message::SelectOpponent(computer) => {
+                self.computer = computer;
+                iced::Task::none()
+            }
+            Message::SelectTimeControl(time_control) => {
+                self.time_control = time_control;
+                iced::Task::none()
+            }
+            Message::CustomMinutesChanged(minutes) => {
+                self.custom_minutes = minutes;
+                self.select_custom_time_control();
+                iced::Task::none()
+            }
+            Message::CustomIncrementChanged(increment) => {
+                self.custom_increment = increment;
+                self.select_custom_time_control();
+                iced::Task::none()
+            }
+            Message::SelectIncrementKind(kind) => {
+                self.custom_kind = kind;
+                self.select_custom_time_control();
+                iced::Task::none()
+            }
+            Message::Tick(now) => {
+                self.check_flag(now);
+                iced::Task::none()
+            }
+            Message::EngineMoved(search_id, engine_move) => {
+                if search_id != self.search_id {
+                    return iced::Task::none();
+                }
+
+                self.engine_thinking = false;
+
+                if let Some(engine_move) = engine_move
+                    && self.outcome.is_none()
+                {
+                    self.play_move(engine_move);
+                }
+
+                iced::Task::none()
+            }
+        }
+    }
+
+    /*
+     * A click on a move target of the selected piece plays the move, or asks for the promotion piece. Any other
+     * click selects the clicked piece if it belongs to the side to move and can move, and clears the selection
+     * otherwise.
+     */
+    fn click_square(&mut self, square: usize) -> Task<Message> {
+        let Some(game) = self.current_position().cloned() else {
+            unreachable!("Should only be able to move when initialized!")
+        };
+
+        let legal_moves = game.legal_moves();
+
+        if let Some(from) = self.selected_square.take() {
+            if from == square {
+                return Task::none();
+            }
+
+            let clicked_move = legal_moves.iter().find(|legal_move| {
+                legal_move.from == from && is_move_to(&game, **legal_move, square)
+            });
+
+            match clicked_move.map(|legal_move| (legal_move, legal_move.move_type)) {
+                Some((
+                    _,
+                    MoveType::PawnMove {
+                        promotion_move: Some(_),
+                    },
+                )) => {
+                    // The selection stays, it tells which pawn promotes.
+                    self.selected_square = Some(from);
+                    self.promotion_choice = Some((game.side_to_move, square));
+
+                    return Task::none();
+                }
+                Some((legal_move, _)) => {
+                    self.play_move(*legal_move);
+
+                    return self.request_engine_move();
+                }
+                None => {}
+            }
+        }
+
+        if legal_moves
+            .iter()
+            .any(|legal_move| legal_move.from == square)
+        {
+            self.selected_square = Some(square);
+        }
+
+        Task::none()
+    }
+
+    /*
+     * Dropping a dragged piece plays the move like clicking the piece and then the target square. A drop that is
+     * not a legal move leaves the position as it was, so the piece snaps back.
+     */
+    fn drop_piece(&mut self, from: usize, to: usize) -> Task<Message> {
+        let Some(game) = self.current_position() else {
+            unreachable!("Should only be able to move when initialized!")
+        };
+
+        let is_legal = game
+            .legal_moves()
+            .iter()
+            .any(|legal_move| legal_move.from == from && is_move_to(game, *legal_move, to));
+
+        if !is_legal {
+            return Task::none();
+        }
+
+        self.selected_square = Some(from);
+        self.click_square(to)
+    }
+
+    /* The squares of the pieces of the side to move that have a legal move, none while looking back or after the game. */
+    pub fn movable_squares(&self) -> Bitboard {
+        if self.is_viewing_history() || self.outcome.is_some() {
+            return 0;
+        }
+
+        self.current_position().map_or(0, |game| {
+            game.legal_moves().iter().fold(0, |squares, legal_move| {
+                squares | square_bit(legal_move.from)
+            })
+        })
+    }
+
+    /*
+     * The marks of every square of the shown position, indexed like ChessBoard::board: the selected piece with the
+     * targets of its legal moves, the squares of the move that led to it and the king of the side to move when it is
+     * in check.
+     */
+    pub fn square_highlights(&self) -> [SquareHighlight; 64] {
+        let mut highlights = [SquareHighlight::default(); 64];
+
+        let Some(game) = self.viewed_position() else {
+            return highlights;
+        };
+
+        if let Some(last_move) = self.viewed_move().map(|played| played.legal_move) {
+            highlights[last_move.from].last_move = true;
+            highlights[last_move.to].last_move = true;
+        }
+
+        if game.is_checked() {
+            let king_position = match game.side_to_move {
+                Players::White => game.white_king_position,
+                Players::Black => game.black_king_position,
+            };
+
+            highlights[king_position].checked_king = true;
+        }
+
+        if let Some(from) = self.selected_square {
+            highlights[from].selected = true;
+
+            for legal_move in game
+                .legal_moves()
+                .iter()
+                .filter(|legal_move| legal_move.from == from)
+            {
+                highlights[legal_move.to].target = Some(if legal_move.is_capture {
+                    MoveTarget::Capture
+                } else {
+                    MoveTarget::Quiet
+                });
+            }
+        }
+
+        highlights
+    }
+
+    /* The moves of the game so far. */
+    pub fn moves(&self) -> &[PlayedMove] {
+        &self.moves
+    }
+
+    /* The lines given up for the moves of the game. */
+    pub fn variations(&self) -> &[Variation] {
+        &self.variations
+    }
+
+    /* The number of moves that led to the shown position. */
+    pub fn viewed_ply(&self) -> usize {
+        self.viewed_ply
+    }
+
+    /* The position the game continues from. */
+    fn current_position(&self) -> Option<&ChessBoard> {
+        self.positions.last()
+    }
+
+    fn viewed_position(&self) -> Option<&ChessBoard> {
+        self.positions.get(self.viewed_ply)
+    }
+
+    /* The move that led to the shown position, None at the start. */
+    fn viewed_move(&self) -> Option<&PlayedMove> {
+        self.viewed_ply
+            .checked_sub(1)
+            .and_then(|index| self.moves.get(index))
+    }
+
+    fn is_viewing_history(&self) -> bool {
+        self.viewed_ply < self.moves.len()
+    }
+
+    /* Whether the board takes moves: the game goes on, the player is on turn and looks at the current position. */
+    fn can_move(&self) -> bool {
+        self.outcome.is_none() && !self.is_engine_turn() && !self.is_viewing_history()
+    }
+
+    /* The board stays on the current position while the player picks a promotion piece. */
+    fn navigate(&mut self, navigation: Navigation) {
+        if self.promotion_choice.is_some() {
+            return;
+        }
+
+        let last = self.moves.len();
+
+        self.viewed_ply = match navigation {
+            Navigation::First => 0,
+            Navigation::Previous => self.viewed_ply.saturating_sub(1),
+            Navigation::Next => (self.viewed_ply + 1).min(last),
+            Navigation::Last => last,
+            Navigation::Ply(ply) => ply.min(last),
+        };
+
+        self.selected_square = None;
+    }
+
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        self.outcome
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /* The time control typed in on the start screen, None while it is not a valid one. */
+    fn custom_time_control(&self) -> Option<TimeControl> {
+        let minutes: f64 = self.custom_minutes.trim().parse().ok()?;
+        let increment: f64 = self.custom_increment.trim().parse().ok()?;
+
+        Some(TimeControl {
+            base: Duration::try_from_secs_f64(minutes * 60.)
+                .ok()
+                .filter(|base| !base.is_zero())?,
+            increment: Duration::try_from_secs_f64(increment).ok()?,
+            kind: self.custom_kind,
+        })
+    }
+
+    fn select_custom_time_control(&mut self) {
+        if let Some(time_control) = self.custom_time_control() {
+            self.time_control = Some(time_control);
+        }
+    }
+
+    /* Whether moves may be taken back, which is not the case once the players or the clock decided the game. */
+    fn can_take_back(&self) -> bool {
+        self.outcome
+            .is_none_or(|outcome| outcome.is_decided_by_position())
+    }
+
+    /* Ends the game when the side to move ran out of time. Returns whether it did. */
+    fn check_flag(&mut self, now: Instant) -> bool {
+        let Some(flagged) = self.clock.as_ref().and_then(|clock| clock.flagged(now)) else {
+            return false;
+        };
+
+        let game = self
+            .current_position()
+            .expect("The clock only runs during a game");
+
+        self.end_game(timeout_outcome(&game.board, flagged), now);
+
+        true
+    }
+
+    /* Ends the game before the position decides it, for a draw, resignation or timeout. */
+    fn end_game(&mut self, outcome: GameOutcome, now: Instant) {
+        self.outcome = Some(outcome);
+        self.claimable_draw = None;
+        self.promotion_choice = None;
+        self.selected_square = None;
+        self.cancel_search();
+        self.sync_clock(now);
+    }
+
+    /*
+     * The game as PGN, with the names of the players, the time control in seconds and how the game ended when it
+     * is over.
+     */
+    pub fn pgn(&self) -> Option<PgnGame> {
+        let start = self.positions.first()?;
+        let mut pgn = PgnGame::new(start.clone());
+
+        let computer_name = self
+            .computer
+            .map(|strength| format!("Computer ({strength})"));
+        let name = |side: Players| match &computer_name {
+            Some(computer) if side != self.player_colour => computer.clone(),
+            _ => String::from("Player"),
+        };
+
+        pgn.set_tag("Event", "Casual game");
+        pgn.set_tag("White", &name(Players::White));
+        pgn.set_tag("Black", &name(Players::Black));
+
+        if let Some(clock) = &self.clock {
+            let time_control = clock.time_control();
+            pgn.set_tag(
+                "TimeControl",
+                &format!(
+                    "{}+{}",
+                    time_control.base.as_secs(),
+                    time_control.increment.as_secs()
+                ),
+            );
+        }
+
+        pgn.moves = pgn_moves(&self.moves);
+        attach_variations(&mut pgn.moves, 0, &self.variations);
+
+        if let Some(outcome) = self.outcome {
+            let termination = match outcome {
+                GameOutcome::Timeout { .. } | GameOutcome::TimeoutVsInsufficientMaterial => {
+                    "time forfeit"
+                }
+                _ => "normal",
+            };
+
+            pgn.set_tag("Termination", termination);
+            pgn.set_result(PgnResult::from(outcome));
+        }
+
+        Some(pgn)
+    }
+
+    /* Writes the game to a new file in the working directory, named after the time it was saved. */
+    fn save_pgn(&self) -> std::io::Result<PathBuf> {
+        let pgn = self
+            .pgn()
+            .ok_or_else(|| std::io::Error::other("There is no game to save"))?;
+
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let path = PathBuf::from(format!("chess-game-{seconds}.pgn"));
+
+        fs::write(&path, pgn.to_string())?;
+
+        Ok(path)
+    }
+
+    /* Runs the clock of the side to move, or stops the clocks once the game is over. */
+    fn sync_clock(&mut self, now: Instant) {
+        let side_to_move = self.current_position().map(|game| game.side_to_move);
+
+        let Some(clock) = &mut self.clock else {
+            return;
+        };
+
+        match side_to_move {
+            Some(side) if self.outcome.is_none() => clock.start(side, now),
+            _ => clock.stop(now),
+        }
+    }
+
+    fn start_game(&mut self, board: ChessBoard) -> Task<Message> {
+        self.positions = vec![board];
+        self.moves.clear();
+        self.viewed_ply = 0;
+        self.undone_moves.clear();
+        self.variations.clear();
+        self.outcome = None;
+        self.claimable_draw = None;
+        self.selected_square = None;
+        self.promotion_choice = None;
+        if self.computer.is_some() {
+            self.perspective = self.player_colour;
+        }
+        self.clock = self.time_control.map(Clock::new);
+        self.clocks.clear();
+        self.undone_clocks.clear();
+        self.export_status = None;
+        self.save_clock(Instant::now());
+        self.sync_clock(Instant::now());
+        self.cancel_search();
+        self.transposition_table.clear();
+        self.request_engine_move()
+    }
+
+    fn is_engine_turn(&self) -> bool {
+        self.computer.is_some()
+            && self
+                .current_position()
+                .is_some_and(|game| game.side_to_move != self.player_colour)
+    }
+
+    /*
+     * Start a search on its own thread when it is the computer's turn, so the UI keeps running while it thinks.
+     * The result comes back as Message::EngineMoved.
+     */
+    fn request_engine_move(&mut self) -> Task<Message> {
+        let Some(strength) = self.computer else {
+            return Task::none();
+        };
+
+        if !self.is_engine_turn() || self.outcome.is_some() {
+            return Task::none();
+        }
+
+        let game = self
+            .current_position()
+            .cloned()
+            .expect("Checked in is_engine_turn");
+
+        let mut limits = strength.search_limits(Arc::clone(&self.search_stop));
+        let search_id = self.search_id;
+
+        // The positions before the current one, so the computer knows which moves repeat them.
+        limits.history = self.positions[..self.positions.len() - 1]
+            .iter()
+            .map(|position| position.zobrist_key)
+            .collect();
+
+        if let Some(clock) = &self.clock {
+            let budget = clock.remaining(game.side_to_move, Instant::now()) / ENGINE_TIME_SHARE
+                + clock.time_control().increment / 2;
+
+            limits.time = Some(limits.time.map_or(budget, |time| time.min(budget)));
+        }
+
+        let table = Arc::clone(&self.transposition_table);
+        let (sender, receiver) = oneshot::channel();
+
+        thread::spawn(move || {
+            // The receiver is gone if the game was closed, then nobody needs the move.
+            _ = sender.send(game.search(&limits, &table).best_move);
+        });
+
+        self.engine_thinking = true;
+
+        Task::perform(
+            async move { receiver.await.ok().flatten() },
+            move |engine_move| Message::EngineMoved(search_id, engine_move),
+        )
+    }
+
+    /* Stops a running search and makes sure its result is ignored. */
+    fn cancel_search(&mut self) {
+        self.search_stop.store(true, Ordering::SeqCst);
+        self.search_stop = Arc::new(AtomicBool::new(false));
+        self.search_id += 1;
+        self.engine_thinking = false;
+    }
+
+    /*
+     * Plays a new move. Unless it is the next undone move, the undone moves are kept as a variation and are no longer
+     * available for redo. The move comes too late if the clock of the side to move ran out, then the game is lost on
+     * time instead.
+     */
+    fn play_move(&mut self, legal_move: LegalMove) {
+        let now = Instant::now();
+
+        if self.check_flag(now) {
+            return;
+        }
+
+        if self.undone_moves.last() == Some(&legal_move) {
+            self.undone_moves.pop();
+            self.undone_clocks.pop();
+        } else {
+            self.keep_undone_moves();
+        }
+        self.push_move(legal_move);
+
+        if let Some(clock) = &mut self.clock {
+            clock.switch(now);
+        }
+        self.save_clock(now);
+        self.sync_clock(now);
+    }
+
+    /*
+     * Keeps the clock as the current position was reached. It is stopped, so restoring it later charges nobody for the
+     * time in between.
+     */
+    fn save_clock(&mut self, now: Instant) {
+        if let Some(clock) = &self.clock {
+            let mut saved = clock.clone();
+            saved.stop(now);
+            self.clocks.push(saved);
+        }
+    }
+
+    /*
+     * Turns the undone moves into a variation branching off the current position. The variations that branched off the
+     * undone moves go with it.
+     */
+    fn keep_undone_moves(&mut self) {
+        let undone_moves = std::mem::take(&mut self.undone_moves);
+        self.undone_clocks.clear();
+        let Some(mut position) = self.current_position().cloned() else {
+            return;
+        };
+
+        if undone_moves.is_empty() {
+            return;
+        }
+
+        let ply = self.moves.len();
+        let moves = undone_moves
+            .into_iter()
+            .rev()
+            .map(|legal_move| {
+                let san = position.move_to_san(legal_move);
+                position.make_move(legal_move);
+                PlayedMove { legal_move, san }
+            })
+            .collect();
+
+        let (variations, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.variations)
+            .into_iter()
+            .partition(|variation| variation.ply > ply);
+
+        self.variations = kept;
+        self.variations.push(Variation {
+            ply,
+            moves,
+            variations,
+        });
+        self.variations.sort_by_key(|variation| variation.ply);
+    }
+
+    /*
+     * Makes the move and checks whether it ended the game. Claimable draws are only offered to the player. Someone
+     * looking back at an earlier position keeps looking at it.
+     */
+    fn push_move(&mut self, legal_move: LegalMove) {
+        let game = self
+            .current_position()
+            .expect("Should only move when initialized!");
+
+        let san = game.move_to_san(legal_move);
+        let mut position = game.clone();
+        position.make_move(legal_move);
+
+        let follow_game = !self.is_viewing_history();
+
+        self.positions.push(position);
+        self.moves.push(PlayedMove { legal_move, san });
+
+        if follow_game {
+            self.viewed_ply = self.moves.len();
+        }
+
+        self.update_outcome();
+        self.selected_square = None;
+    }
+
+    fn update_outcome(&mut self) {
+        let (game, previous_positions) = self
+            .positions
+            .split_last()
+            .expect("Should only move when initialized!");
+
+        self.outcome = None;
+        self.claimable_draw = None;
+        match game.outcome(previous_positions) {
+            Some(outcome) if outcome.is_claimable() => self.claimable_draw = Some(outcome),
+            outcome => self.outcome = outcome,
+        }
+    }
+
+    /* Returns false if there was no move to undo. */
+    fn undo_move(&mut self) -> bool {
+        let Some(played) = self.moves.pop() else {
+            return false;
+        };
+
+        self.positions.pop();
+        self.undone_moves.push(played.legal_move);
+
+        // The clock goes back to how it was in the position the game returns to.
+        if let Some(clock) = self.clocks.pop() {
+            self.undone_clocks.push(clock);
+            self.clock = self.clocks.last().cloned();
+        }
+        self.viewed_ply = self.viewed_ply.min(self.moves.len());
+
+        true
+    }
+
+    /* Returns false if there was no move to redo. */
+    fn redo_move(&mut self) -> bool {
+        match self.undone_moves.pop() {
+            Some(legal_move) => {
+                self.push_move(legal_move);
+
+                if let Some(clock) = self.undone_clocks.pop() {
+                    self.clock = Some(clock.clone());
+                    self.clocks.push(clock);
+                }
+                true
+            }
+            None => false,
         }
     }
 
+    fn after_history_change(&mut self) -> Task<Message> {
+        self.selected_square = None;
+        self.promotion_choice = None;
+        self.viewed_ply = self.moves.len();
+
+        if !self.positions.is_empty() {
+            self.update_outcome();
+        }
+
+        self.sync_clock(Instant::now());
+        self.request_engine_move()
+    }
+
     pub fn theme(&self) -> iced::Theme {
         iced::Theme::Dark
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        match &self.game {
+        match self.current_position() {
             Some(_) => {
                 if let Some(window_size) = self.window_size {
                     const SCALE: f32 = 0.8;
@@ -245,17 +1149,53 @@ impl ChessGame {
                     let square_length = iced::Length::Fixed(board_size / 8.);
                     let board_length = iced::Length::Fixed(board_size);
 
-                    let top_bar: iced::widget::Container<Message> = container(
-                        row![
-                            button(text("Reset board")).on_press(Message::Reset),
-                            button(text("Switch perspective")).on_press(Message::SwitchPerspective)
-                        ]
-                        .spacing(15),
-                    )
-                    .padding(15);
+                    let mut top_bar_buttons = row![
+                        button(text("Reset board")).on_press(Message::Reset),
+                        button(text("New 960 game")).on_press(Message::NewChess960Game),
+                        button(text("Switch perspective")).on_press(Message::SwitchPerspective),
+                        button(text("Undo")).on_press_maybe(
+                            (self.can_take_back() && !self.moves.is_empty())
+                                .then_some(Message::Undo)
+                        ),
+                        button(text("Redo")).on_press_maybe(
+                            (self.can_take_back() && !self.undone_moves.is_empty())
+                                .then_some(Message::Redo)
+                        ),
+                    ]
+                    .spacing(15);
+
+                    if self.is_viewing_history() && self.can_take_back() {
+                        top_bar_buttons = top_bar_buttons
+                            .push(button(text("Play from here")).on_press(Message::PlayFromHere));
+                    }
+
+                    if self.engine_thinking {
+                        top_bar_buttons = top_bar_buttons.push(text("Computer is thinking..."));
+                    }
+
+                    if let Some(draw) = self.claimable_draw {
+                        top_bar_buttons = top_bar_buttons.push(
+                            button(text(format!("Claim draw ({draw})")))
+                                .on_press(Message::ClaimDraw),
+                        );
+                    }
+
+                    if self.outcome.is_none() {
+                        top_bar_buttons =
+                            top_bar_buttons.push(button(text("Resign")).on_press(Message::Resign));
+
+                        // Only two players at one board can agree to a draw, the computer plays on.
+                        if self.computer.is_none() {
+                            top_bar_buttons = top_bar_buttons
+                                .push(button(text("Agree draw")).on_press(Message::AgreeDraw));
+                        }
+                    }
+
+                    let top_bar: iced::widget::Container<Message> =
+                        container(top_bar_buttons).padding(15);
 
                     let game = if self.promotion_choice.is_some() {
-                        let board = render_board(self, square_length, false);
+                        let board = render_board(self, board_size, false);
                         let promotions = render_promotions(self, square_length);
                         row![
                             board,
@@ -265,17 +1205,24 @@ impl ChessGame {
                                 .center_x(iced::Length::Fixed((board_size / 8.) + 45.))
                         ]
                     } else {
-                        row![render_board(self, square_length, true)]
+                        row![render_board(self, board_size, self.can_move())]
                     };
 
-                    column![
+                    let game_view = column![
                         top_bar,
-                        container(game)
-                            .height(board_length)
-                            .width(iced::Length::Fixed(board_size * 1.25))
-                            .center(Fill)
-                    ]
-                    .into()
+                        row![
+                            container(game)
+                                .height(board_length)
+                                .width(iced::Length::Fixed(board_size * 1.25))
+                                .center(Fill),
+                            render_side_panel(self, board_length),
+                        ]
+                    ];
+
+                    match self.outcome {
+                        Some(outcome) => stack![game_view, render_game_over(self, outcome)].into(),
+                        None => game_view.into(),
+                    }
                 } else {
                     text!("Waiting for window size!").into()
                 }
@@ -294,6 +1241,7 @@ impl ChessGame {
 
                 container(column![
                     starting_text,
+                    render_new_game_options(self),
                     container(row![start_button, exit_button].spacing(40)).center(Fill)
                 ])
                 .padding(iced::Padding::from(100.))
@@ -311,42 +1259,356 @@ impl ChessGame {
     }
 }
 
+/*
+ * Castling can also be played by clicking the rook, the only way to castle in Chess960 when the king stays put.
+ */
+fn is_move_to(game: &ChessBoard, legal_move: LegalMove, square: usize) -> bool {
+    match legal_move.move_type {
+        MoveType::CastleKingSide | MoveType::CastleQueenSide => {
+            legal_move.to == square || game.castling_rook_squares(legal_move).0 == square
+        }
+        _ => legal_move.to == square,
+    }
+}
+
+fn pgn_moves(moves: &[PlayedMove]) -> Vec<PgnMove> {
+    moves
+        .iter()
+        .map(|played| PgnMove::new(played.san.clone(), played.legal_move))
+        .collect()
+}
+
+/* Adds the variations to the moves they replace, the first of the moves being played at position first_ply. */
+fn attach_variations(moves: &mut [PgnMove], first_ply: usize, variations: &[Variation]) {
+    for variation in variations {
+        let Some(replaced) = variation
+            .ply
+            .checked_sub(first_ply)
+            .and_then(|index| moves.get_mut(index))
+        else {
+            continue;
+        };
+
+        let mut variation_moves = pgn_moves(&variation.moves);
+        attach_variations(&mut variation_moves, variation.ply, &variation.variations);
+
+        replaced.variations.push(PgnVariation {
+            moves: variation_moves,
+            ..PgnVariation::default()
+        });
+    }
+}
+
+fn render_new_game_options(state: &ChessGame) -> iced::widget::Column<'_, Message> {
+    let option_button = |label: String, is_selected: bool, message: Message| {
+        button(text(label).size(24))
+            .padding(Padding::from([10, 20]))
+            .style(if is_selected {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(message)
+    };
+
+    let colours = row![
+        text("Play as").size(24).width(Length::Fixed(150.)),
+        option_button(
+            String::from("White"),
+            state.player_colour == Players::White,
+            Message::SelectColour(Players::White)
+        ),
+        option_button(
+            String::from("Black"),
+            state.player_colour == Players::Black,
+            Message::SelectColour(Players::Black)
+        ),
+    ]
+    .spacing(15);
+
+    let mut opponents = row![
+        text("Opponent").size(24).width(Length::Fixed(150.)),
+        option_button(
+            String::from("Human"),
+            state.computer.is_none(),
+            Message::SelectOpponent(None)
+        ),
+    ]
+    .spacing(15);
+
+    for strength in STRENGTHS {
+        opponents = opponents.push(option_button(
+            format!("Computer ({strength})"),
+            state.computer == Some(strength),
+            Message::SelectOpponent(Some(strength)),
+        ));
+    }
+
+    let mut time_controls = row![
+        text("Time").size(24).width(Length::Fixed(150.)),
+        option_button(
+            String::from("No clock"),
+            state.time_control.is_none(),
+            Message::SelectTimeControl(None)
+        ),
+    ]
+    .spacing(15);
+
+    for time_control in TIME_CONTROLS {
+        time_controls = time_controls.push(option_button(
+            time_control.to_string(),
+            state.time_control == Some(time_control),
+            Message::SelectTimeControl(Some(time_control)),
+        ));
+    }
+
+    let custom_time_control = state.custom_time_control();
+    let is_custom_selected = state.time_control.is_some_and(|time_control| {
+        Some(time_control) == custom_time_control && !TIME_CONTROLS.contains(&time_control)
+    });
+
+    let custom = row![
+        text("Custom").size(24).width(Length::Fixed(150.)),
+        text_input("Minutes", &state.custom_minutes)
+            .on_input(Message::CustomMinutesChanged)
+            .size(24)
+            .width(Length::Fixed(120.)),
+        text("+").size(24),
+        text_input("Seconds", &state.custom_increment)
+            .on_input(Message::CustomIncrementChanged)
+            .size(24)
+            .width(Length::Fixed(120.)),
+        option_button(
+            String::from("Increment"),
+            is_custom_selected && state.custom_kind == IncrementKind::Fischer,
+            Message::SelectIncrementKind(IncrementKind::Fischer)
+        ),
+        option_button(
+            String::from("Delay"),
+            is_custom_selected && state.custom_kind == IncrementKind::Bronstein,
+            Message::SelectIncrementKind(IncrementKind::Bronstein)
+        ),
+    ]
+    .spacing(15)
+    .align_y(iced::Center);
+
+    column![colours, opponents, time_controls, custom]
+        .spacing(15)
+        .padding(15)
+}
+
+/*
+ * The result of a finished game above the board, which stays visible around it so the moves can still be looked
+ * through.
+ */
+fn render_game_over(state: &ChessGame, outcome: GameOutcome) -> Element<'_, Message> {
+    let headline = match outcome.winner() {
+        Some(Players::White) => "White wins",
+        Some(Players::Black) => "Black wins",
+        None => "Draw",
+    };
+
+    let buttons = row![
+        button(text("Rematch")).on_press(Message::Rematch),
+        button(text("Start screen")).on_press(Message::ShowStartScreen),
+        button(text("Save PGN")).on_press(Message::SavePgn),
+        button(text("Copy FEN")).on_press(Message::CopyFen),
+    ]
+    .spacing(15);
+
+    let mut result = column![
+        text(headline).size(48),
+        text(format!(
+            "{}  {}",
+            PgnResult::from(outcome),
+            outcome.reason()
+        ))
+        .size(24),
+        buttons,
+    ]
+    .spacing(20)
+    .align_x(iced::Center);
+
+    if let Some(status) = &state.export_status {
+        result = result.push(text(status));
+    }
+
+    center(opaque(
+        container(result).padding(30).style(container::rounded_box),
+    ))
+    .into()
+}
+
 fn render_board<'a>(
     state: &'a ChessGame,
-    square_size: Length,
+    board_size: f32,
     can_update: bool,
-) -> iced::widget::Column<'a, Message> {
-    let mut board_columns = iced::widget::Column::new();
-    let mut board_rows = iced::widget::Row::new();
-
-    let board_state = state
-        .game
-        .to_owned()
+) -> Element<'a, Message> {
+    let game = state
+        .viewed_position()
         .expect("Expect the board here from calling function!");
 
-    for i in 0..64 {
-        board_rows = board_rows.push(get_button_from_square(
-            i,
-            board_state.board[get_corrected_index(i, state.perspective)],
-            &state.piece_sprite,
-            state.perspective,
-            state.selected_square,
-            square_size,
-            can_update,
-        ));
-        if i % 8 == 7 {
-            board_columns = board_columns.push(board_rows);
-            board_rows = Row::new();
+    canvas(BoardCanvas {
+        board: &game.board,
+        highlights: state.square_highlights(),
+        movable: state.movable_squares(),
+        perspective: state.perspective,
+        pieces: &state.piece_sprite,
+        can_update,
+    })
+    .width(board_size)
+    .height(board_size)
+    .into()
+}
+
+/*
+ * The move list, with the clocks above and below it in a timed game. The clock at the bottom belongs to the side
+ * at the bottom of the board.
+ */
+fn render_side_panel(state: &ChessGame, height: Length) -> Element<'_, Message> {
+    let Some(clock) = &state.clock else {
+        return render_move_list(state, height);
+    };
+
+    let now = Instant::now();
+    let (top, bottom) = match state.perspective {
+        Players::White => (Players::Black, Players::White),
+        Players::Black => (Players::White, Players::Black),
+    };
+
+    column![
+        render_clock(clock, top, now),
+        render_move_list(state, Fill),
+        render_clock(clock, bottom, now),
+    ]
+    .spacing(10)
+    .height(height)
+    .into()
+}
+
+fn render_clock<'a>(clock: &Clock, side: Players, now: Instant) -> Element<'a, Message> {
+    let name = match side {
+        Players::White => "White",
+        Players::Black => "Black",
+    };
+
+    container(
+        text(format!(
+            "{name}  {}",
+            format_time(clock.remaining(side, now))
+        ))
+        .size(32),
+    )
+    .padding(10)
+    .width(Length::Fixed(260.))
+    .style(if clock.running() == Some(side) {
+        container::primary
+    } else {
+        container::rounded_box
+    })
+    .into()
+}
+
+/*
+ * The moves in standard algebraic notation, numbered in pairs. Clicking a move shows the position after it, the
+ * move of the shown position is highlighted. The lines given up for the game follow in parentheses.
+ */
+fn render_move_list(state: &ChessGame, height: Length) -> Element<'_, Message> {
+    let Some(start) = state.positions.first() else {
+        return column![].into();
+    };
+
+    let move_button = |ply: usize, san: &str| {
+        button(text(san.to_string()))
+            .width(Length::Fixed(90.))
+            .style(if ply == state.viewed_ply {
+                button::primary
+            } else {
+                button::text
+            })
+            .on_press(Message::Navigate(Navigation::Ply(ply)))
+    };
+
+    // A game from a position with black to move starts its first pair with an empty white move.
+    let black_starts = start.side_to_move == Players::Black;
+    let mut move_list = column![].spacing(4);
+    let mut ply = 0;
+
+    while ply < state.moves.len() {
+        let number = start.full_move_counter as usize + (ply + black_starts as usize) / 2;
+        let mut pair = row![text(format!("{number}.")).width(Length::Fixed(40.))].spacing(5);
+
+        if ply == 0 && black_starts {
+            pair = pair.push(text("...").width(Length::Fixed(90.)));
+        } else {
+            pair = pair.push(move_button(ply + 1, &state.moves[ply].san));
+            ply += 1;
+        }
+
+        if let Some(played) = state.moves.get(ply) {
+            pair = pair.push(move_button(ply + 1, &played.san));
+            ply += 1;
         }
+
+        move_list = move_list.push(pair);
     }
-    board_columns
+
+    let mut variations: Vec<(usize, &Variation)> = state
+        .variations
+        .iter()
+        .rev()
+        .map(|variation| (0, variation))
+        .collect();
+
+    while let Some((depth, variation)) = variations.pop() {
+        let line = variation_text(start, variation);
+        move_list = move_list.push(
+            container(text(format!("({line})")).size(14))
+                .padding(Padding::ZERO.left(15. * depth as f32)),
+        );
+
+        variations.extend(
+            variation
+                .variations
+                .iter()
+                .rev()
+                .map(|nested| (depth + 1, nested)),
+        );
+    }
+
+    container(scrollable(move_list.padding(10)).height(height))
+        .width(Length::Fixed(260.))
+        .style(container::rounded_box)
+        .into()
+}
+
+/* A variation in one line, numbered like the moves of the game, e.g. "3... Nc6 4. Bb5". */
+fn variation_text(start: &ChessBoard, variation: &Variation) -> String {
+    let black_starts = start.side_to_move == Players::Black;
+
+    variation
+        .moves
+        .iter()
+        .enumerate()
+        .map(|(index, played)| {
+            let ply = variation.ply + index + black_starts as usize;
+            let number = start.full_move_counter as usize + ply / 2;
+
+            match (ply.is_multiple_of(2), index == 0) {
+                (true, _) => format!("{number}. {}", played.san),
+                (false, true) => format!("{number}... {}", played.san),
+                (false, false) => played.san.clone(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 fn render_promotions<'a>(
     state: &'a ChessGame,
     square_size: Length,
 ) -> iced::widget::Column<'a, Message> {
-    let game = state.game.as_ref().unwrap();
+    let game = state.current_position().unwrap();
 
     let pieces_to_render = if game.side_to_move == Players::White {
         [WQUEEN, WROOK, WBISHOP, WKNIGHT]
@@ -372,97 +1634,11 @@ fn render_promotions<'a>(
     column(svg_pieces).spacing(15)
 }
 
-fn get_button_from_square<'a>(
-    position: usize,
-    square: i8,
-    pieces: &'a SvgPieces,
-    perspective: Players,
-    selected_square: Option<usize>,
-    square_size: Length,
-    can_update: bool,
-) -> iced::widget::Button<'a, Message> {
-    let correct_index = get_corrected_index(position, perspective);
-
-    let button = if can_update {
-        match square {
-            WKING | BKING | WQUEEN | BQUEEN | WROOK | BROOK | WBISHOP | BBISHOP | WKNIGHT
-            | BKNIGHT | WPAWN | BPAWN => button(
-                pieces
-                    .to_iced_svg(square)
-                    .width(square_size)
-                    .height(square_size)
-                    .content_fit(ContentFit::Cover),
-            )
-            .width(square_size)
-            .height(square_size)
-            .on_press(Message::ClickedSquare(correct_index)),
-
-            EMPTY => button(text(" "))
-                .width(square_size)
-                .height(square_size)
-                .on_press(Message::ClickedSquare(correct_index)),
-            _ => unreachable!("not allowed as square type/value"),
-        }
-    } else {
-        match square {
-            WKING | BKING | WQUEEN | BQUEEN | WROOK | BROOK | WBISHOP | BBISHOP | WKNIGHT
-            | BKNIGHT | WPAWN | BPAWN => button(
-                pieces
-                    .to_iced_svg(square)
-                    .width(square_size)
-                    .height(square_size)
-                    .content_fit(ContentFit::Cover),
-            )
-            .width(square_size)
-            .height(square_size),
-
-            EMPTY => button(text(" ")).width(square_size).height(square_size),
-            _ => unreachable!("not allowed as square type/value"),
-        }
-    };
-
-    if should_be_light_square(correct_index) {
-        button.style(move |theme: &iced::Theme, status| {
-            let palette = theme.palette();
-
-            if selected_square.is_some() && selected_square.expect("msg") == correct_index {
-                button::Style::default().with_background(palette.text.scale_alpha(0.5))
-            } else {
-                match status {
-                    button::Status::Active | button::Status::Disabled => {
-                        button::Style::default().with_background(palette.text)
-                    }
-                    button::Status::Hovered => {
-                        button::Style::default().with_background(palette.text.scale_alpha(0.7))
-                    }
-                    _ => button::primary(theme, status),
-                }
-            }
-        })
-    } else {
-        button.style(move |theme: &iced::Theme, status| {
-            let palette = theme.extended_palette();
-
-            if selected_square.is_some() && selected_square.expect("msg") == correct_index {
-                button::Style::default().with_background(palette.primary.base.color)
-            } else {
-                match status {
-                    button::Status::Active | button::Status::Disabled => {
-                        button::Style::default().with_background(palette.primary.strong.color)
-                    }
-
-                    button::Status::Hovered => {
-                        button::Style::default().with_background(palette.primary.weak.color)
-                    }
-
-                    _ => button::primary(theme, status),
-                }
-            }
-        })
-    }
-}
-
-fn get_corrected_index(index: usize, perspective: Players) -> usize {
+/*
+ * Converts between the index of a square as shown, row by row from the top left, and its index on the board.
+ * Converting twice gives the index back.
+ */
+pub fn get_corrected_index(index: usize, perspective: Players) -> usize {
     match perspective {
         Players::White => {
             let column = 7 - (index / 8);
@@ -478,15 +1654,3 @@ fn get_corrected_index(index: usize, perspective: Players) -> usize {
         }
     }
 }
-
-pub fn should_be_light_square(position: usize) -> bool {
-    let row = (position / 8) + 1;
-
-    let col = (position % 8) + 1;
-
-    if row.is_multiple_of(2) {
-        !col.is_multiple_of(2)
-    } else {
-        col.is_multiple_of(2)
-    }
-}
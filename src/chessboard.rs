@@ -1,10 +1,19 @@
+use crate::bitboard::Bitboards;
+use crate::engine::ChessEngine;
 use crate::fen::parsing::{
     parse_castling_ability, parse_epawn, parse_fen_part, parse_position, parse_side_to_move,
     parse_string_to_num,
 };
-use crate::fen::{FEN_STARTING_POSITION, Fen, FenArguments, FenError, FenType};
-use crate::moves::{LegalMove, MoveType};
+use crate::fen::writing::{
+    write_castling_ability, write_epawn, write_position, write_side_to_move,
+};
+use crate::fen::{FEN_STARTING_POSITION, Fen, FenArguments, FenError, FenErrorKind, FenType};
+use crate::moves::{
+    LegalMove, LegalMoveParseError, LegalMoveParseErrorKind, MoveType, square_to_string,
+};
+use crate::zobrist::{castling_key, en_passant_key, piece_key, side_to_move_key, zobrist_key};
 use std::fmt;
+use std::str::FromStr;
 
 pub const BOARD_HEIGHT: usize = 8;
 pub const BOARD_WIDTH: usize = 8;
@@ -63,21 +72,147 @@ pub enum Players {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ChessBoard {
     pub board: [i8; BOARD_WIDTH * BOARD_HEIGHT],
+    pub bitboards: Bitboards, /* Mirrors board, change squares with set_square to keep them in sync */
     pub white_king_position: usize,
     pub black_king_position: usize,
     pub side_to_move: Players,
-    pub castling_ability: [bool; 4], /* WKing, WQueen, BKing, BQueen */
+    pub castling_ability: [Option<usize>; 4], /* The file of the rook to castle with: WKing, WQueen, BKing, BQueen */
     pub en_passant_target_square: Option<usize>,
     pub half_move_clock: u32,
     pub full_move_counter: u32,
+    pub zobrist_key: u64, /* Polyglot compatible, kept up to date by set_square and make_move */
+}
+
+/*
+ * The state make_move cannot recover from the move itself, needed to unmake the move again.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SavedState {
+    pub captured_piece: i8, /* EMPTY if the move was not a capture */
+    pub castling_ability: [Option<usize>; 4],
+    pub en_passant_target_square: Option<usize>,
+    pub half_move_clock: u32,
+    pub white_king_position: usize,
+    pub black_king_position: usize,
+    pub zobrist_key: u64,
 }
 
 impl ChessBoard {
-    pub fn make_move(&mut self, move_to_make: LegalMove) {
+    /*
+     * Resolve a UCI LAN string (e.g. e2e4, e7e8q or e1g1) against the legal moves of this position.
+     */
+    pub fn parse_uci_move(&self, uci_move: &str) -> Result<LegalMove, LegalMoveParseError> {
+        self.parse_uci_notation(uci_move, false)
+    }
+
+    /*
+     * Like parse_uci_move, but castling is written as the king taking its own rook (e.g. e1h1) the way UCI_Chess960
+     * expects, since in Chess960 the king may otherwise end on a square it can also step to.
+     */
+    pub fn parse_chess960_uci_move(
+        &self,
+        uci_move: &str,
+    ) -> Result<LegalMove, LegalMoveParseError> {
+        self.parse_uci_notation(uci_move, true)
+    }
+
+    /*
+     * The UCI string of a legal move of this position, see parse_chess960_uci_move for the Chess960 castling notation.
+     */
+    pub fn uci_move_string(&self, legal_move: LegalMove, chess960: bool) -> String {
+        match legal_move.move_type {
+            MoveType::CastleKingSide | MoveType::CastleQueenSide if chess960 => {
+                let (rook_from, _) = self.castling_rook_squares(legal_move);
+
+                format!(
+                    "{}{}",
+                    square_to_string(legal_move.from),
+                    square_to_string(rook_from)
+                )
+            }
+            _ => legal_move.to_string(),
+        }
+    }
+
+    fn parse_uci_notation(
+        &self,
+        uci_move: &str,
+        chess960: bool,
+    ) -> Result<LegalMove, LegalMoveParseError> {
+        let parsed_move = LegalMove::from_str(uci_move)?;
+
+        let promotion = match parsed_move.move_type {
+            MoveType::PawnMove { promotion_move } => promotion_move,
+            _ => None,
+        };
+
+        let mut candidates = self
+            .legal_moves()
+            .into_iter()
+            .filter(|legal_move| {
+                let to = match legal_move.move_type {
+                    MoveType::CastleKingSide | MoveType::CastleQueenSide if chess960 => {
+                        self.castling_rook_squares(*legal_move).0
+                    }
+                    _ => legal_move.to,
+                };
+
+                legal_move.from == parsed_move.from && to == parsed_move.to
+            })
+            .peekable();
+
+        let is_promotion = candidates.peek().is_some_and(|legal_move| {
+            matches!(
+                legal_move.move_type,
+                MoveType::PawnMove {
+                    promotion_move: Some(_)
+                }
+            )
+        });
+
+        if is_promotion && promotion.is_none() {
+            return Err(LegalMoveParseError::new(
+                LegalMoveParseErrorKind::MissingPromotion,
+                uci_move.to_string(),
+            ));
+        }
+
+        candidates
+            .find(|legal_move| match legal_move.move_type {
+                MoveType::PawnMove { promotion_move } => promotion_move == promotion,
+                _ => promotion.is_none(),
+            })
+            .ok_or_else(|| {
+                LegalMoveParseError::new(LegalMoveParseErrorKind::Illegal, uci_move.to_string())
+            })
+    }
+
+    /*
+     * Play the move on the board and return what is needed to unmake it with unmake_move.
+     */
+    pub fn make_move(&mut self, move_to_make: LegalMove) -> SavedState {
+        let captured_piece = match move_to_make.move_type {
+            MoveType::Enpassant { target_square } => self.board[target_square],
+            _ => self.board[move_to_make.to],
+        };
+
+        let saved_state = SavedState {
+            captured_piece,
+            castling_ability: self.castling_ability,
+            en_passant_target_square: self.en_passant_target_square,
+            half_move_clock: self.half_move_clock,
+            white_king_position: self.white_king_position,
+            black_king_position: self.black_king_position,
+            zobrist_key: self.zobrist_key,
+        };
+
+        // The pieces are hashed by set_square, the rest of the state is hashed out here and back in after the move.
+        self.zobrist_key ^= self.state_zobrist_key();
+
         match move_to_make.move_type {
             MoveType::Normal => {
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
+                self.set_square(move_to_make.to, self.board[move_to_make.from]);
+                self.set_square(move_to_make.from, EMPTY);
                 self.update_half_moves();
                 self.reset_enpassant();
             }
@@ -88,33 +223,33 @@ impl ChessBoard {
                     Some(piece) => piece,
                 };
 
-                self.board[move_to_make.to] = piece_to_set;
-                self.board[move_to_make.from] = EMPTY;
+                self.set_square(move_to_make.to, piece_to_set);
+                self.set_square(move_to_make.from, EMPTY);
 
                 self.reset_enpassant();
                 self.reset_half_moves();
             }
 
             MoveType::PawnDoubleMove => {
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
+                self.set_square(move_to_make.to, self.board[move_to_make.from]);
+                self.set_square(move_to_make.from, EMPTY);
 
                 self.set_enpassant(move_to_make.to);
                 self.reset_half_moves();
             }
 
             MoveType::Enpassant { target_square } => {
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
-                self.board[target_square] = EMPTY;
+                self.set_square(move_to_make.to, self.board[move_to_make.from]);
+                self.set_square(move_to_make.from, EMPTY);
+                self.set_square(target_square, EMPTY);
 
                 self.reset_enpassant();
                 self.reset_half_moves();
             }
 
             MoveType::RookMove => {
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
+                self.set_square(move_to_make.to, self.board[move_to_make.from]);
+                self.set_square(move_to_make.from, EMPTY);
 
                 self.update_half_moves();
                 self.check_and_update_rook(move_to_make.from);
@@ -128,61 +263,184 @@ impl ChessBoard {
                     self.black_king_position = move_to_make.to;
                 }
 
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
+                self.set_square(move_to_make.to, self.board[move_to_make.from]);
+                self.set_square(move_to_make.from, EMPTY);
                 self.update_half_moves();
                 self.reset_enpassant();
                 self.disable_castle();
             }
 
-            MoveType::CastleKingSide => {
-                let rook_pos_before = move_to_make.from + 3;
+            MoveType::CastleKingSide | MoveType::CastleQueenSide => {
+                let (rook_from, rook_to) = self.castling_rook_squares(move_to_make);
+                let king = self.board[move_to_make.from];
+                let rook = self.board[rook_from];
 
-                if self.board[move_to_make.from].is_positive() {
+                if king.is_positive() {
                     self.white_king_position = move_to_make.to;
                 } else {
                     self.black_king_position = move_to_make.to;
                 }
 
-                self.board[move_to_make.from + 1] = self.board[rook_pos_before];
-                self.board[rook_pos_before] = EMPTY;
-
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
+                // In Chess960 the king and the rook may land on each other's square, so both leave first.
+                self.set_square(move_to_make.from, EMPTY);
+                self.set_square(rook_from, EMPTY);
+                self.set_square(move_to_make.to, king);
+                self.set_square(rook_to, rook);
 
                 self.disable_castle();
                 self.update_half_moves();
                 self.reset_enpassant();
             }
+        }
 
-            MoveType::CastleQueenSide => {
-                let rook_pos_before = move_to_make.from - 4;
+        if move_to_make.is_capture {
+            self.reset_half_moves();
+            self.check_and_update_rook(move_to_make.to);
+        }
 
-                if self.board[move_to_make.from].is_positive() {
-                    self.white_king_position = move_to_make.to;
-                } else {
-                    self.black_king_position = move_to_make.to;
-                }
+        self.update_full_move();
+        self.update_side_to_move();
 
-                self.board[move_to_make.from - 1] = self.board[rook_pos_before];
-                self.board[rook_pos_before] = EMPTY;
+        self.zobrist_key ^= self.state_zobrist_key();
 
-                self.board[move_to_make.to] = self.board[move_to_make.from];
-                self.board[move_to_make.from] = EMPTY;
+        saved_state
+    }
 
-                self.disable_castle();
-                self.update_half_moves();
-                self.reset_enpassant();
-            }
+    /*
+     * Take back the last move made, saved_state must be the value make_move returned for it.
+     */
+    pub fn unmake_move(&mut self, move_to_unmake: LegalMove, saved_state: SavedState) {
+        self.update_side_to_move();
+
+        if self.side_to_move == Players::Black {
+            self.full_move_counter -= 1;
         }
 
-        if move_to_make.is_capture {
-            self.reset_half_moves();
-            self.check_and_update_rook(move_to_make.to);
+        let from = move_to_unmake.from;
+        let to = move_to_unmake.to;
+
+        match move_to_unmake.move_type {
+            MoveType::Enpassant { target_square } => {
+                self.set_square(from, self.board[to]);
+                self.set_square(to, EMPTY);
+                self.set_square(target_square, saved_state.captured_piece);
+            }
+
+            MoveType::PawnMove {
+                promotion_move: Some(_),
+            } => {
+                let pawn = match self.side_to_move {
+                    Players::White => WPAWN,
+                    Players::Black => BPAWN,
+                };
+
+                self.set_square(from, pawn);
+                self.set_square(to, saved_state.captured_piece);
+            }
+
+            MoveType::CastleKingSide | MoveType::CastleQueenSide => {
+                self.castling_ability = saved_state.castling_ability;
+
+                let (rook_from, rook_to) = self.castling_rook_squares(move_to_unmake);
+                let king = self.board[to];
+                let rook = self.board[rook_to];
+
+                self.set_square(to, EMPTY);
+                self.set_square(rook_to, EMPTY);
+                self.set_square(from, king);
+                self.set_square(rook_from, rook);
+            }
+
+            _ => {
+                self.set_square(from, self.board[to]);
+                self.set_square(to, saved_state.captured_piece);
+            }
         }
 
-        self.update_full_move();
+        self.castling_ability = saved_state.castling_ability;
+        self.en_passant_target_square = saved_state.en_passant_target_square;
+        self.half_move_clock = saved_state.half_move_clock;
+        self.white_king_position = saved_state.white_king_position;
+        self.black_king_position = saved_state.black_king_position;
+        self.zobrist_key = saved_state.zobrist_key;
+    }
+
+    /*
+     * Pass the turn to the other side without moving, for null-move pruning. Undo it with unmake_null_move.
+     */
+    pub fn make_null_move(&mut self) -> SavedState {
+        let saved_state = SavedState {
+            captured_piece: EMPTY,
+            castling_ability: self.castling_ability,
+            en_passant_target_square: self.en_passant_target_square,
+            half_move_clock: self.half_move_clock,
+            white_king_position: self.white_king_position,
+            black_king_position: self.black_king_position,
+            zobrist_key: self.zobrist_key,
+        };
+
+        self.zobrist_key ^= self.state_zobrist_key();
+
+        self.reset_enpassant();
+        self.update_half_moves();
+        self.update_side_to_move();
+
+        self.zobrist_key ^= self.state_zobrist_key();
+
+        saved_state
+    }
+
+    pub fn unmake_null_move(&mut self, saved_state: SavedState) {
         self.update_side_to_move();
+
+        self.en_passant_target_square = saved_state.en_passant_target_square;
+        self.half_move_clock = saved_state.half_move_clock;
+        self.zobrist_key = saved_state.zobrist_key;
+    }
+
+    /*
+     * Put a piece, or EMPTY, on a square of the mailbox board and the bitboards, and update the zobrist key.
+     */
+    pub fn set_square(&mut self, position: usize, piece: i8) {
+        let old_piece = self.board[position];
+
+        if old_piece != EMPTY {
+            self.bitboards.toggle_piece(position, old_piece);
+            self.zobrist_key ^= piece_key(position, old_piece);
+        }
+
+        if piece != EMPTY {
+            self.bitboards.toggle_piece(position, piece);
+            self.zobrist_key ^= piece_key(position, piece);
+        }
+
+        self.board[position] = piece;
+    }
+
+    /*
+     * The square the rook of a castling move by the side to move starts on and the square it ends on, taken from the
+     * castling rights of the position before the move.
+     */
+    pub fn castling_rook_squares(&self, castle: LegalMove) -> (usize, usize) {
+        let king_side = castle.move_type == MoveType::CastleKingSide;
+        let side = self.side_to_move;
+
+        let rook_file = self.castling_ability[castling_index(side, king_side)]
+            .expect("A castling move needs the castling right");
+        let rook_to_file = if king_side { 5 } else { 3 };
+
+        (back_rank(side) + rook_file, back_rank(side) + rook_to_file)
+    }
+
+    fn state_zobrist_key(&self) -> u64 {
+        castling_key(&self.castling_ability)
+            ^ en_passant_key(self)
+            ^ side_to_move_key(self.side_to_move)
+    }
+
+    fn with_zobrist_key(mut chessboard: ChessBoard) -> ChessBoard {
+        chessboard.zobrist_key = zobrist_key(&chessboard);
+        chessboard
     }
 
     fn set_enpassant(&mut self, location: usize) {
@@ -218,37 +476,26 @@ impl ChessBoard {
     }
 
     fn disable_castle(&mut self) {
-        match self.side_to_move {
-            Players::White => {
-                self.castling_ability[0] = false;
-                self.castling_ability[1] = false;
-            }
-            Players::Black => {
-                self.castling_ability[2] = false;
-                self.castling_ability[3] = false;
-            }
-        }
-    }
-
-    fn check_and_update_rook(&mut self, captured_square: usize) {
-        match captured_square {
-            0 => {
-                self.castling_ability[1] = false;
-            }
+        let king_side = castling_index(self.side_to_move, true);
 
-            7 => {
-                self.castling_ability[0] = false;
-            }
-
-            56 => {
-                self.castling_ability[3] = false;
-            }
+        self.castling_ability[king_side] = None;
+        self.castling_ability[king_side + 1] = None;
+    }
 
-            63 => {
-                self.castling_ability[2] = false;
+    /*
+     * A rook that moves or is captured on its starting square takes its castling right with it.
+     */
+    fn check_and_update_rook(&mut self, square: usize) {
+        for (index, rook_file) in self.castling_ability.iter_mut().enumerate() {
+            let side = if index < 2 {
+                Players::White
+            } else {
+                Players::Black
+            };
+
+            if *rook_file == Some(square.wrapping_sub(back_rank(side))) {
+                *rook_file = None;
             }
-
-            _ => {}
         }
     }
 
@@ -260,6 +507,41 @@ impl ChessBoard {
     }
 }
 
+/* The index of the first square on the rank the pieces of a side start on. */
+pub const fn back_rank(side: Players) -> usize {
+    match side {
+        Players::White => 0,
+        Players::Black => 56,
+    }
+}
+
+/* The index of a castling right in castling_ability. */
+pub const fn castling_index(side: Players, king_side: bool) -> usize {
+    let side_offset = match side {
+        Players::White => 0,
+        Players::Black => 2,
+    };
+
+    if king_side {
+        side_offset
+    } else {
+        side_offset + 1
+    }
+}
+
+/* Whether a square is light, a1 being dark. */
+pub fn should_be_light_square(position: usize) -> bool {
+    let row = (position / 8) + 1;
+
+    let col = (position % 8) + 1;
+
+    if row.is_multiple_of(2) {
+        !col.is_multiple_of(2)
+    } else {
+        col.is_multiple_of(2)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Move {
     pub dx: i8,
@@ -297,7 +579,7 @@ impl Fen for ChessBoard {
 
         let castling_ability = parse_fen_part(
             &mut fen_state,
-            parse_castling_ability,
+            |str_part| parse_castling_ability(str_part, &board),
             FenArguments::CastlingAbility,
         )?;
 
@@ -307,35 +589,26 @@ impl Fen for ChessBoard {
             FenArguments::EnPassantTargetSquare,
         )?;
 
-        if fen_type == FenType::NoCounter {
-            return Ok(Self {
-                board,
-                white_king_position: find_first_matching_piece(&board, WKING)
-                    .expect("A king should exist on validated FEN"),
-                black_king_position: find_first_matching_piece(&board, BKING)
-                    .expect("A king should exist on validated FEN"),
-                side_to_move,
-                castling_ability,
-                en_passant_target_square,
-                half_move_clock: 0,
-                full_move_counter: 0,
-            });
-        }
-
-        let half_move_clock = parse_fen_part(
-            &mut fen_state,
-            parse_string_to_num,
-            FenArguments::HalfMoveClock,
-        )?;
-
-        let full_move_counter = parse_fen_part(
-            &mut fen_state,
-            parse_string_to_num,
-            FenArguments::FullMoveCounter,
-        )?;
+        let (half_move_clock, full_move_counter) = if fen_type == FenType::NoCounter {
+            (0, 1)
+        } else {
+            (
+                parse_fen_part(
+                    &mut fen_state,
+                    parse_string_to_num,
+                    FenArguments::HalfMoveClock,
+                )?,
+                parse_fen_part(
+                    &mut fen_state,
+                    parse_string_to_num,
+                    FenArguments::FullMoveCounter,
+                )?,
+            )
+        };
 
-        Ok(Self {
+        let chessboard = Self::with_zobrist_key(Self {
             board,
+            bitboards: Bitboards::from_board(&board),
             white_king_position: find_first_matching_piece(&board, WKING)
                 .expect("A king should exist on validated FEN"),
             black_king_position: find_first_matching_piece(&board, BKING)
@@ -345,7 +618,37 @@ impl Fen for ChessBoard {
             en_passant_target_square,
             half_move_clock,
             full_move_counter,
-        })
+            zobrist_key: 0,
+        });
+
+        // The side to move could take the other king, no game can reach that position.
+        let (opponent_king_position, opponent) = match side_to_move {
+            Players::White => (chessboard.black_king_position, Players::Black),
+            Players::Black => (chessboard.white_king_position, Players::White),
+        };
+        let checkers = chessboard.attackers_to(opponent_king_position)
+            & !chessboard.bitboards.colour(opponent);
+
+        if checkers != 0 {
+            return Err(FenError::new(
+                FenErrorKind::SideNotToMoveInCheck,
+                fen.to_string(),
+            ));
+        }
+
+        Ok(chessboard)
+    }
+
+    fn to_fen(&self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            write_position(&self.board),
+            write_side_to_move(self.side_to_move),
+            write_castling_ability(&self.castling_ability, &self.board),
+            write_epawn(self.en_passant_target_square),
+            self.half_move_clock,
+            self.full_move_counter
+        )
     }
 }
 
@@ -410,7 +713,7 @@ fn parse_chessboard_to_string(board: &[i8]) -> Vec<String> {
     printable_board
 }
 
-fn piece_to_char(piece: i8) -> char {
+pub fn piece_to_char(piece: i8) -> char {
     match piece {
         WPAWN => 'P',
         BPAWN => 'p',
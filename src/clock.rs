@@ -0,0 +1,151 @@
+use crate::bitboard::colour_index;
+use crate::chessboard::Players;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub const TIME_CONTROLS: [TimeControl; 5] = [
+    TimeControl::fischer(1, 0),
+    TimeControl::fischer(3, 2),
+    TimeControl::fischer(5, 3),
+    TimeControl::fischer(15, 10),
+    TimeControl::fischer(30, 20),
+];
+
+/*
+ * How the time of a move is made up for. A Fischer increment is added after every move, a Bronstein delay gives
+ * back the time used for the move, but no more than the delay.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IncrementKind {
+    Fischer,
+    Bronstein,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeControl {
+    pub base: Duration, // The time each side starts with.
+    pub increment: Duration,
+    pub kind: IncrementKind,
+}
+
+impl TimeControl {
+    pub const fn fischer(minutes: u64, increment_seconds: u64) -> Self {
+        TimeControl {
+            base: Duration::from_secs(minutes * 60),
+            increment: Duration::from_secs(increment_seconds),
+            kind: IncrementKind::Fischer,
+        }
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.base.as_secs_f64() / 60.;
+        let increment = self.increment.as_secs_f64();
+
+        match self.kind {
+            IncrementKind::Fischer => write!(f, "{minutes}+{increment}"),
+            IncrementKind::Bronstein => write!(f, "{minutes} d{increment}"),
+        }
+    }
+}
+
+/*
+ * The clocks of both sides. At most one of them runs, the time it used is only taken off when it stops or the
+ * move is made, so the remaining time is always asked for at a point in time.
+ */
+#[derive(Debug, Clone)]
+pub struct Clock {
+    time_control: TimeControl,
+    remaining: [Duration; 2], // Indexed by colour_index, without the time of the running turn.
+    running: Option<(Players, Instant)>, // The side whose clock runs and when it started.
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl) -> Self {
+        Clock {
+            time_control,
+            remaining: [time_control.base; 2],
+            running: None,
+        }
+    }
+
+    pub fn time_control(&self) -> TimeControl {
+        self.time_control
+    }
+
+    pub fn running(&self) -> Option<Players> {
+        self.running.map(|(side, _)| side)
+    }
+
+    pub fn remaining(&self, side: Players, now: Instant) -> Duration {
+        let stored = self.remaining[colour_index(side)];
+
+        match self.running {
+            Some((running, since)) if running == side => {
+                stored.saturating_sub(now.saturating_duration_since(since))
+            }
+            _ => stored,
+        }
+    }
+
+    /* The side whose clock runs if its time is up. */
+    pub fn flagged(&self, now: Instant) -> Option<Players> {
+        let side = self.running()?;
+
+        self.remaining(side, now).is_zero().then_some(side)
+    }
+
+    /* Runs the clock of a side without any increment, stopping the other one. */
+    pub fn start(&mut self, side: Players, now: Instant) {
+        if self.running() != Some(side) {
+            self.stop(now);
+            self.running = Some((side, now));
+        }
+    }
+
+    pub fn stop(&mut self, now: Instant) {
+        if let Some(side) = self.running() {
+            self.remaining[colour_index(side)] = self.remaining(side, now);
+            self.running = None;
+        }
+    }
+
+    /* The side whose clock runs made its move: it gets its increment and the clock of the other side starts. */
+    pub fn switch(&mut self, now: Instant) {
+        let Some((side, since)) = self.running else {
+            return;
+        };
+
+        let used = now.saturating_duration_since(since);
+        let bonus = match self.time_control.kind {
+            IncrementKind::Fischer => self.time_control.increment,
+            IncrementKind::Bronstein => used.min(self.time_control.increment),
+        };
+
+        self.remaining[colour_index(side)] = self.remaining(side, now) + bonus;
+
+        let opponent = match side {
+            Players::White => Players::Black,
+            Players::Black => Players::White,
+        };
+        self.running = Some((opponent, now));
+    }
+}
+
+/*
+ * A remaining time as shown on a clock: hours only when there are any, and tenths of a second in the last ten
+ * seconds.
+ */
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else if time < Duration::from_secs(10) {
+        format!("0:{seconds:02}.{}", time.subsec_millis() / 100)
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
@@ -1,384 +1,458 @@
+pub mod attacks;
 mod bishop;
+pub mod exchange;
 mod king;
 mod knight;
+mod magic;
 mod pawn;
 mod queen;
 mod rook;
-mod utils;
 use bishop::get_bishop_moves;
+use exchange::static_exchange;
 use king::get_king_moves;
 use knight::get_knight_moves;
 use pawn::get_pawn_moves;
 use queen::get_queen_moves;
 use rook::get_rook_moves;
 
-use crate::chessboard::{
-    BBISHOP, BKING, BKNIGHT, BPAWN, BQUEEN, BROOK, ChessBoard, EMPTY, Move, Players, WBISHOP,
-    WKING, WKNIGHT, WPAWN, WQUEEN, WROOK,
-};
-use crate::engine::pawn::{BPAWN_ATTACK_MOVES, WPAWN_ATTACK_MOVES};
-use crate::moves::{
-    ANTI_DIAGONAL_MOVES, DIAGONAL_MOVES, KNIGHT_MOVES, LegalMove, MoveType, concat_const_arrays,
+use attacks::{
+    BETWEEN, BISHOP_LINES, KING_ATTACKS, KNIGHT_ATTACKS, LINE, PAWN_ATTACKS, ROOK_LINES,
+    bishop_attacks, rook_attacks,
 };
 
+use crate::bitboard::{Bitboard, Bitboards, colour_index, square_bit, squares};
+use crate::chessboard::{ChessBoard, Players, WBISHOP, WKING, WKNIGHT, WPAWN, WQUEEN, WROOK};
+use crate::moves::{LegalMove, MoveType};
+use crate::outcome::{GameOutcome, get_outcome};
+use crate::search::{SearchLimits, SearchResult, iterative_deepening};
+use crate::transposition::{PerftTable, TranspositionTable};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
 pub trait ChessEngine {
     fn legal_moves(&self) -> Vec<LegalMove>;
 
     fn perft(&self, depth: u64) -> (Vec<(String, u64)>, u64);
+
+    /* The same counts as perft, with the node counts of subtrees reached by transposition taken from the table. */
+    fn perft_with_table(&self, depth: u64, table: &PerftTable) -> (Vec<(String, u64)>, u64);
+
+    /* The same counts as perft, with the root moves split over the given number of threads. */
+    fn parallel_perft(
+        &self,
+        depth: u64,
+        threads: usize,
+        table: Option<&PerftTable>,
+    ) -> (Vec<(String, u64)>, u64);
+
+    fn is_checked(&self) -> bool;
+
+    /* The pieces of both colours that attack the square, including the king and pieces pinned to it. */
+    fn attackers_to(&self, square: usize) -> Bitboard;
+
+    /*
+     * Static exchange evaluation: the material in centipawns the side to move wins or loses with the move once all
+     * captures on its square are resolved, positive for a winning capture.
+     */
+    fn see(&self, legal_move: LegalMove) -> i32;
+
+    /* previous_positions holds the positions played before this one, oldest first. */
+    fn outcome(&self, previous_positions: &[ChessBoard]) -> Option<GameOutcome>;
+
+    /* The table may hold entries of earlier searches, which are reused, so it can be kept from move to move. */
+    fn search(&self, limits: &SearchLimits, table: &TranspositionTable) -> SearchResult;
 }
 
 impl ChessEngine for ChessBoard {
     fn legal_moves(&self) -> Vec<LegalMove> {
-        let mut legal_moves: Vec<LegalMove> = Vec::with_capacity(20);
-
-        let pseudo_legal_moves = get_pseudo_legal_moves(self);
-
-        for pseudo_legal_move in pseudo_legal_moves {
-            let mut new_board = self.clone();
-            new_board.make_move(pseudo_legal_move);
-
-            let king_pos = if new_board.side_to_move == Players::White {
-                new_board.black_king_position
-            } else {
-                new_board.white_king_position
-            };
-
-            if pseudo_legal_move.move_type == MoveType::CastleKingSide
-                || pseudo_legal_move.move_type == MoveType::CastleQueenSide
-            {
-                // We need to also check the squares the king would pass by for any checks as that also invalidates the move
-                let passed_squares = if pseudo_legal_move.move_type == MoveType::CastleKingSide {
-                    [king_pos - 1, king_pos - 2]
-                } else {
-                    [king_pos + 1, king_pos + 2]
-                };
-
-                let pos_1 = king_is_checked(&new_board.board, passed_squares[0], self.side_to_move);
-                let pos_2 = king_is_checked(&new_board.board, passed_squares[1], self.side_to_move);
-
-                if pos_1 || pos_2 {
-                    continue;
-                }
-            }
-
-            if !king_is_checked(&new_board.board, king_pos, self.side_to_move) {
-                legal_moves.push(pseudo_legal_move);
-            }
-        }
-
-        legal_moves
+        generate_legal_moves(self)
     }
 
     fn perft(&self, depth: u64) -> (Vec<(String, u64)>, u64) {
-        if depth == 0 {
-            return (vec![], 1);
-        }
+        divide(self, depth, 1, None)
+    }
 
-        let mut move_sum = 0;
-        let mut move_details = Vec::new();
+    fn perft_with_table(&self, depth: u64, table: &PerftTable) -> (Vec<(String, u64)>, u64) {
+        divide(self, depth, 1, Some(table))
+    }
 
-        for legal_move in self.legal_moves() {
-            let mut new_board = self.clone();
-            new_board.make_move(legal_move);
-            let (_, count) = new_board.perft(depth - 1);
+    fn parallel_perft(
+        &self,
+        depth: u64,
+        threads: usize,
+        table: Option<&PerftTable>,
+    ) -> (Vec<(String, u64)>, u64) {
+        divide(self, depth, threads, table)
+    }
 
-            move_sum += count;
+    fn is_checked(&self) -> bool {
+        let king_pos = if self.side_to_move == Players::White {
+            self.white_king_position
+        } else {
+            self.black_king_position
+        };
 
-            move_details.push((legal_move.to_string(), count));
-        }
+        king_is_checked(&self.bitboards, king_pos, self.side_to_move)
+    }
+
+    fn attackers_to(&self, square: usize) -> Bitboard {
+        attackers_to(&self.bitboards, square, self.bitboards.occupied)
+    }
+
+    fn see(&self, legal_move: LegalMove) -> i32 {
+        static_exchange(self, legal_move)
+    }
 
-        move_details.sort_by(|a, b| a.0.cmp(&b.0));
+    fn outcome(&self, previous_positions: &[ChessBoard]) -> Option<GameOutcome> {
+        get_outcome(
+            self,
+            &self.legal_moves(),
+            self.is_checked(),
+            previous_positions,
+        )
+    }
 
-        (move_details, move_sum)
+    fn search(&self, limits: &SearchLimits, table: &TranspositionTable) -> SearchResult {
+        iterative_deepening(self, limits, table, |_| {})
     }
 }
 
-fn get_pseudo_legal_moves(chessboard: &ChessBoard) -> Vec<LegalMove> {
-    let mut legal_moves: Vec<LegalMove> = Vec::with_capacity(30);
+/*
+ * What restricts the moves of the side to move, computed once per position so only legal moves are generated.
+ * Pieces other than the king only generate moves to the squares allowed by the check mask, and by the line
+ * through them and the king when pinned.
+ */
+struct MoveRestrictions {
+    check_mask: Bitboard, /* The squares a move other than a king move must land on, all squares when not in check */
+    pinned: Bitboard, /* Own pieces that may only move along the line through them and the king */
+    enemy_attacks: Bitboard, /* The squares attacked by the opponent, seen through the own king */
+}
 
-    for (index, square) in chessboard.board.iter().enumerate() {
-        if *square == EMPTY {
-            continue;
-        }
+fn get_move_restrictions(chessboard: &ChessBoard, king_position: usize) -> MoveRestrictions {
+    let bitboards = &chessboard.bitboards;
+    let side = chessboard.side_to_move;
+    let enemy = opponent(side);
+
+    let diagonal_sliders = bitboards.pieces(enemy, WBISHOP) | bitboards.pieces(enemy, WQUEEN);
+    let straight_sliders = bitboards.pieces(enemy, WROOK) | bitboards.pieces(enemy, WQUEEN);
+
+    let mut checkers = (PAWN_ATTACKS[colour_index(side)][king_position]
+        & bitboards.pieces(enemy, WPAWN))
+        | (KNIGHT_ATTACKS[king_position] & bitboards.pieces(enemy, WKNIGHT));
+    let mut pinned = 0;
 
-        if !is_owned_piece(*square, chessboard.side_to_move) {
-            continue;
+    // A slider on a line with the king gives check with nothing in between, and pins a lone own piece in between.
+    let snipers = (BISHOP_LINES[king_position] & diagonal_sliders)
+        | (ROOK_LINES[king_position] & straight_sliders);
+
+    for sniper in squares(snipers) {
+        let blockers = BETWEEN[king_position][sniper] & bitboards.occupied;
+
+        if blockers == 0 {
+            checkers |= square_bit(sniper);
+        } else if blockers.count_ones() == 1 && blockers & bitboards.colour(side) != 0 {
+            pinned |= blockers;
         }
+    }
 
-        match *square {
-            WKING | BKING => {
-                legal_moves.append(&mut get_king_moves(index, chessboard));
-            }
-            WQUEEN | BQUEEN => {
-                legal_moves.append(&mut get_queen_moves(index, chessboard));
-            }
+    let check_mask = match checkers.count_ones() {
+        0 => !0,
+        1 => checkers | BETWEEN[king_position][checkers.trailing_zeros() as usize],
+        _ => 0, /* Only the king can escape a double check */
+    };
 
-            WROOK | BROOK => {
-                legal_moves.append(&mut get_rook_moves(index, chessboard));
-            }
+    // The king is left out of the occupancy, otherwise it could step back along the ray of a checking slider.
+    let enemy_attacks = attacked_squares(
+        bitboards,
+        enemy,
+        bitboards.occupied & !square_bit(king_position),
+    );
+
+    MoveRestrictions {
+        check_mask,
+        pinned,
+        enemy_attacks,
+    }
+}
 
-            WBISHOP | BBISHOP => {
-                legal_moves.append(&mut get_bishop_moves(index, chessboard));
-            }
+/*
+ * Every square attacked by the pieces of the given side, with sliders blocked by the given occupancy.
+ */
+fn attacked_squares(bitboards: &Bitboards, side: Players, occupied: Bitboard) -> Bitboard {
+    let mut attacks = 0;
 
-            WKNIGHT | BKNIGHT => {
-                legal_moves.append(&mut get_knight_moves(index, chessboard));
-            }
+    for position in squares(bitboards.pieces(side, WPAWN)) {
+        attacks |= PAWN_ATTACKS[colour_index(side)][position];
+    }
 
-            WPAWN | BPAWN => {
-                legal_moves.append(&mut get_pawn_moves(index, chessboard));
-            }
+    for position in squares(bitboards.pieces(side, WKNIGHT)) {
+        attacks |= KNIGHT_ATTACKS[position];
+    }
 
-            EMPTY => {
-                continue;
-            }
+    for position in squares(bitboards.pieces(side, WBISHOP) | bitboards.pieces(side, WQUEEN)) {
+        attacks |= bishop_attacks(position, occupied);
+    }
 
-            _ => unreachable!("All cases have been covered!"),
-        }
+    for position in squares(bitboards.pieces(side, WROOK) | bitboards.pieces(side, WQUEEN)) {
+        attacks |= rook_attacks(position, occupied);
     }
 
-    legal_moves
+    for position in squares(bitboards.pieces(side, WKING)) {
+        attacks |= KING_ATTACKS[position];
+    }
+
+    attacks
 }
 
-fn is_owned_piece(piece: i8, current_side: Players) -> bool {
-    if current_side == Players::White {
-        piece.is_positive()
+/*
+ * Generate the legal moves of the side to move. Checks and pins are worked out up front, so no move has to be
+ * played to test its legality.
+ */
+pub fn generate_legal_moves(chessboard: &ChessBoard) -> Vec<LegalMove> {
+    let mut legal_moves: Vec<LegalMove> = Vec::with_capacity(40);
+
+    let king_position = if chessboard.side_to_move == Players::White {
+        chessboard.white_king_position
     } else {
-        piece.is_negative()
+        chessboard.black_king_position
+    };
+
+    let restrictions = get_move_restrictions(chessboard, king_position);
+
+    get_king_moves(
+        king_position,
+        chessboard,
+        restrictions.enemy_attacks,
+        &mut legal_moves,
+    );
+
+    // In double check no other piece can move.
+    if restrictions.check_mask == 0 {
+        return legal_moves;
     }
+
+    let own_pieces =
+        chessboard.bitboards.colour(chessboard.side_to_move) & !square_bit(king_position);
+
+    for index in squares(own_pieces) {
+        let allowed = if restrictions.pinned & square_bit(index) != 0 {
+            restrictions.check_mask & LINE[king_position][index]
+        } else {
+            restrictions.check_mask
+        };
+
+        match chessboard.board[index].abs() {
+            WQUEEN => get_queen_moves(index, chessboard, allowed, &mut legal_moves),
+            WROOK => get_rook_moves(index, chessboard, allowed, &mut legal_moves),
+            WBISHOP => get_bishop_moves(index, chessboard, allowed, &mut legal_moves),
+            WKNIGHT => get_knight_moves(index, chessboard, allowed, &mut legal_moves),
+            WPAWN => get_pawn_moves(index, chessboard, allowed, &mut legal_moves),
+            _ => unreachable!("The bitboards and the board should agree!"),
+        }
+    }
+
+    legal_moves
 }
 
-pub fn get_multi_step_pseudo_legal_moves(
+/*
+ * The node count below every root move, sorted by move. Every thread takes the next root move that is not taken
+ * yet, so a thread that finished a small subtree does not sit idle while another works through a large one.
+ */
+fn divide(
     chessboard: &ChessBoard,
-    move_data: &[Move],
-    position: usize,
-    meta_data: MoveType,
-) -> Vec<LegalMove> {
-    let mut legal_moves: Vec<LegalMove> = Vec::with_capacity(10);
+    depth: u64,
+    threads: usize,
+    table: Option<&PerftTable>,
+) -> (Vec<(String, u64)>, u64) {
+    if depth == 0 {
+        return (vec![], 1);
+    }
 
-    for move_direction in move_data {
-        let mut position_opt = move_direction.get_new_position(position);
+    let root_moves = generate_legal_moves(chessboard);
+    let next_move = AtomicUsize::new(0);
+    let threads = threads.clamp(1, root_moves.len().max(1));
 
-        while position_opt.is_some() {
-            let new_position = position_opt.expect("checked");
-            position_opt = move_direction.get_new_position(new_position);
+    let mut move_details: Vec<(String, u64)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut chessboard = chessboard.clone();
+                    let mut move_details = Vec::new();
 
-            let target_square = chessboard.board[new_position];
+                    while let Some(&legal_move) =
+                        root_moves.get(next_move.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let saved_state = chessboard.make_move(legal_move);
+                        let count = perft_nodes(&mut chessboard, depth - 1, table);
+                        chessboard.unmake_move(legal_move, saved_state);
 
-            let white_is_side_to_move = chessboard.side_to_move == Players::White;
+                        move_details.push((legal_move.to_string(), count));
+                    }
 
-            if target_square != EMPTY && target_square.is_positive() == white_is_side_to_move {
-                break;
-            }
+                    move_details
+                })
+            })
+            .collect();
 
-            let is_capture = target_square != EMPTY;
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("A perft thread panicked"))
+            .collect()
+    });
 
-            legal_moves.push(LegalMove {
-                from: position,
-                to: new_position,
-                move_type: meta_data,
-                is_capture,
-            });
+    move_details.sort_by(|a, b| a.0.cmp(&b.0));
 
-            if is_capture {
-                break;
-            }
-        }
-    }
+    let move_sum = move_details.iter().map(|(_, count)| count).sum();
 
-    legal_moves
+    (move_details, move_sum)
 }
 
-pub fn single_step_get_pseudo_legal_moves(
-    chessboard: &ChessBoard,
-    move_data: &[Move],
-    position: usize,
-    meta_data: MoveType,
-) -> Vec<LegalMove> {
-    let mut legal_moves: Vec<LegalMove> = Vec::with_capacity(4);
+fn perft_nodes(chessboard: &mut ChessBoard, depth: u64, table: Option<&PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
 
-    for move_direction in move_data {
-        let new_position = if let Some(pos) = move_direction.get_new_position(position) {
-            pos
-        } else {
-            continue;
-        };
+    let legal_moves = generate_legal_moves(chessboard);
+
+    if depth == 1 {
+        return legal_moves.len() as u64;
+    }
 
-        let target_square = chessboard.board[new_position];
+    if let Some(nodes) = table.and_then(|table| table.probe(chessboard.zobrist_key, depth)) {
+        return nodes;
+    }
 
-        let white_is_side_to_move = chessboard.side_to_move == Players::White;
+    let mut nodes = 0;
 
-        if target_square != EMPTY && target_square.is_positive() == white_is_side_to_move {
-            continue;
-        }
+    for legal_move in legal_moves {
+        let saved_state = chessboard.make_move(legal_move);
+        nodes += perft_nodes(chessboard, depth - 1, table);
+        chessboard.unmake_move(legal_move, saved_state);
+    }
 
-        let is_capture = target_square != EMPTY;
+    if let Some(table) = table {
+        table.store(chessboard.zobrist_key, depth, nodes);
+    }
+
+    nodes
+}
+
+fn opponent(side: Players) -> Players {
+    match side {
+        Players::White => Players::Black,
+        Players::Black => Players::White,
+    }
+}
 
+/*
+ * Push a move to every attacked square that does not hold a piece of the side to move.
+ */
+pub fn push_moves_to_targets(
+    chessboard: &ChessBoard,
+    position: usize,
+    attacks: Bitboard,
+    move_type: MoveType,
+    legal_moves: &mut Vec<LegalMove>,
+) {
+    let bitboards = &chessboard.bitboards;
+    let enemy_pieces = bitboards.colour(opponent(chessboard.side_to_move));
+    let targets = attacks & !bitboards.colour(chessboard.side_to_move);
+
+    for target in squares(targets) {
         legal_moves.push(LegalMove {
             from: position,
-            to: new_position,
-            move_type: meta_data,
-            is_capture,
+            to: target,
+            move_type,
+            is_capture: enemy_pieces & square_bit(target) != 0,
         });
     }
+}
 
-    legal_moves
+/*
+ * The pieces of both colours that attack the square, with occupied as the blockers of sliding pieces. Removing a
+ * piece from occupied lets the sliders behind it through, which is how x-ray attackers are found.
+ */
+pub fn attackers_to(bitboards: &Bitboards, square: usize, occupied: Bitboard) -> Bitboard {
+    let both = |piece_type| {
+        bitboards.pieces(Players::White, piece_type) | bitboards.pieces(Players::Black, piece_type)
+    };
+
+    let diagonal_sliders = both(WBISHOP) | both(WQUEEN);
+    let straight_sliders = both(WROOK) | both(WQUEEN);
+
+    /* Pawns attack the square from where a pawn of the other colour on it would attack */
+    let white_pawns = PAWN_ATTACKS[colour_index(Players::Black)][square]
+        & bitboards.pieces(Players::White, WPAWN);
+    let black_pawns = PAWN_ATTACKS[colour_index(Players::White)][square]
+        & bitboards.pieces(Players::Black, WPAWN);
+
+    white_pawns
+        | black_pawns
+        | (KNIGHT_ATTACKS[square] & both(WKNIGHT))
+        | (KING_ATTACKS[square] & both(WKING))
+        | (bishop_attacks(square, occupied) & diagonal_sliders)
+        | (rook_attacks(square, occupied) & straight_sliders)
 }
 
-fn king_is_checked(board: &[i8; 64], king_position: usize, king_color: Players) -> bool {
+fn king_is_checked(bitboards: &Bitboards, king_position: usize, king_color: Players) -> bool {
     /* Check per individual pieces, i.e. Diagonal moves: check only Queen, Bishop.. Anti-diagonal moves: check only Queen, Rook.. Pawn attacks */
 
-    king_is_attacked_by_pawns(board, king_position, king_color)
-        || king_is_attacked_by_opposing_king(board, king_position, king_color)
-        || king_is_attacked_on_diagonals(board, king_position, king_color)
-        || king_is_attacked_on_anti_diagonals(board, king_position, king_color)
-        || king_is_attacked_by_knights(board, king_position, king_color)
+    king_is_attacked_by_pawns(bitboards, king_position, king_color)
+        || king_is_attacked_by_opposing_king(bitboards, king_position, king_color)
+        || king_is_attacked_on_diagonals(bitboards, king_position, king_color)
+        || king_is_attacked_on_anti_diagonals(bitboards, king_position, king_color)
+        || king_is_attacked_by_knights(bitboards, king_position, king_color)
 }
 
 pub fn king_is_attacked_by_pawns(
-    board: &[i8; 64],
+    bitboards: &Bitboards,
     king_position: usize,
     king_color: Players,
 ) -> bool {
-    let (attack_moves, pawn) = if king_color == Players::White {
-        (WPAWN_ATTACK_MOVES, BPAWN)
-    } else {
-        (BPAWN_ATTACK_MOVES, WPAWN)
-    };
+    /* A pawn attacks the king exactly when a pawn of the king's colour on the king square would attack the pawn */
+    let attacks = PAWN_ATTACKS[colour_index(king_color)][king_position];
 
-    for attack_move in attack_moves {
-        if let Some(pos) = attack_move.get_new_position(king_position)
-            && pawn == board[pos]
-        {
-            return true;
-        }
-    }
-
-    false
+    attacks & bitboards.pieces(opponent(king_color), WPAWN) != 0
 }
 
 pub fn king_is_attacked_on_diagonals(
-    board: &[i8; 64],
+    bitboards: &Bitboards,
     king_position: usize,
     king_color: Players,
 ) -> bool {
     /* No need to check for anything other than queen and bishops of opposing colors */
-    let moves = DIAGONAL_MOVES;
-
-    let danger_pieces = if king_color == Players::White {
-        [BQUEEN, BBISHOP]
-    } else {
-        [WQUEEN, WBISHOP]
-    };
-
-    for attack_move in moves {
-        let mut pos_opt = attack_move.get_new_position(king_position);
-
-        while pos_opt.is_some() {
-            let new_pos = pos_opt.expect("prechecked for some variant");
-            pos_opt = attack_move.get_new_position(new_pos);
-
-            let square = board[new_pos];
+    let enemy = opponent(king_color);
+    let danger_pieces = bitboards.pieces(enemy, WQUEEN) | bitboards.pieces(enemy, WBISHOP);
 
-            if square == EMPTY {
-                continue;
-            }
-
-            if danger_pieces.contains(&square) {
-                return true;
-            } else {
-                break;
-            }
-        }
-    }
-
-    false
+    BISHOP_LINES[king_position] & danger_pieces != 0
+        && bishop_attacks(king_position, bitboards.occupied) & danger_pieces != 0
 }
 
 pub fn king_is_attacked_by_opposing_king(
-    board: &[i8; 64],
+    bitboards: &Bitboards,
     king_position: usize,
     king_color: Players,
 ) -> bool {
-    let moves = concat_const_arrays(DIAGONAL_MOVES, ANTI_DIAGONAL_MOVES);
-
-    let attack_king = if king_color == Players::White {
-        BKING
-    } else {
-        WKING
-    };
-
-    for attack_move in moves {
-        let pos_opt = attack_move.get_new_position(king_position);
-
-        if let Some(position) = pos_opt {
-            let square = board[position];
-
-            if square == attack_king {
-                return true;
-            }
-        }
-    }
-
-    false
+    KING_ATTACKS[king_position] & bitboards.pieces(opponent(king_color), WKING) != 0
 }
 
 pub fn king_is_attacked_on_anti_diagonals(
-    board: &[i8; 64],
+    bitboards: &Bitboards,
     king_position: usize,
     king_color: Players,
 ) -> bool {
-    /* No need to check for anything other than queen and bishops of opposing colors */
-    let moves = ANTI_DIAGONAL_MOVES;
-
-    let danger_pieces = if king_color == Players::White {
-        [BQUEEN, BROOK]
-    } else {
-        [WQUEEN, WROOK]
-    };
-
-    for attack_move in moves {
-        let mut pos_opt = attack_move.get_new_position(king_position);
-
-        while pos_opt.is_some() {
-            let new_pos = pos_opt.expect("prechecked for some variant");
-            pos_opt = attack_move.get_new_position(new_pos);
+    /* No need to check for anything other than queen and rooks of opposing colors */
+    let enemy = opponent(king_color);
+    let danger_pieces = bitboards.pieces(enemy, WQUEEN) | bitboards.pieces(enemy, WROOK);
 
-            let square = board[new_pos];
-
-            if square == EMPTY {
-                continue;
-            }
-
-            if danger_pieces.contains(&square) {
-                return true;
-            } else {
-                break;
-            }
-        }
-    }
-
-    false
+    ROOK_LINES[king_position] & danger_pieces != 0
+        && rook_attacks(king_position, bitboards.occupied) & danger_pieces != 0
 }
 
 pub fn king_is_attacked_by_knights(
-    board: &[i8; 64],
+    bitboards: &Bitboards,
     king_position: usize,
     king_color: Players,
 ) -> bool {
-    let knight = if king_color == Players::White {
-        BKNIGHT
-    } else {
-        WKNIGHT
-    };
-
-    for attack_move in KNIGHT_MOVES {
-        if let Some(pos) = attack_move.get_new_position(king_position)
-            && knight == board[pos]
-        {
-            return true;
-        }
-    }
-
-    false
+    KNIGHT_ATTACKS[king_position] & bitboards.pieces(opponent(king_color), WKNIGHT) != 0
 }
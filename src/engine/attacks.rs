@@ -0,0 +1,166 @@
+use crate::bitboard::{Bitboard, square_bit};
+use crate::chessboard::Move;
+use crate::moves::{ALL_DIRECTION_MOVES, ANTI_DIAGONAL_MOVES, DIAGONAL_MOVES, KNIGHT_MOVES};
+
+use super::magic::{bishop_magic_attacks, rook_magic_attacks};
+use super::pawn::{BPAWN_ATTACK_MOVES, WPAWN_ATTACK_MOVES};
+
+/*
+ * Attack tables, generated at compile time from the same direction tables the mailbox code uses.
+ */
+pub const KNIGHT_ATTACKS: [Bitboard; 64] = generate_step_attacks(&KNIGHT_MOVES);
+pub const KING_ATTACKS: [Bitboard; 64] = generate_step_attacks(&ALL_DIRECTION_MOVES);
+pub const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    generate_step_attacks(&WPAWN_ATTACK_MOVES),
+    generate_step_attacks(&BPAWN_ATTACK_MOVES),
+]; /* Indexed by colour_index of the attacking pawn */
+
+/* The attacks of a bishop or rook on an empty board, a cheap test whether a slider can attack a square at all. */
+pub const BISHOP_LINES: [Bitboard; 64] = generate_lines(&DIAGONAL_MOVES);
+pub const ROOK_LINES: [Bitboard; 64] = generate_lines(&ANTI_DIAGONAL_MOVES);
+
+/* The squares strictly between two squares on a shared rank, file or diagonal, empty for unaligned squares. */
+pub static BETWEEN: [[Bitboard; 64]; 64] = generate_between();
+/* The whole line through two aligned squares from edge to edge, empty for unaligned squares. */
+pub static LINE: [[Bitboard; 64]; 64] = generate_line();
+
+const fn offset_square(position: usize, direction: Move) -> Option<usize> {
+    let file = (position % 8) as i8 + direction.dx;
+    let rank = (position / 8) as i8 + direction.dy;
+
+    if file < 0 || file > 7 || rank < 0 || rank > 7 {
+        None
+    } else {
+        Some((rank * 8 + file) as usize)
+    }
+}
+
+const fn generate_step_attacks(directions: &[Move]) -> [Bitboard; 64] {
+    let mut attacks = [0; 64];
+    let mut position = 0;
+
+    while position < 64 {
+        let mut index = 0;
+
+        while index < directions.len() {
+            if let Some(target) = offset_square(position, directions[index]) {
+                attacks[position] |= square_bit(target);
+            }
+            index += 1;
+        }
+        position += 1;
+    }
+
+    attacks
+}
+
+const fn generate_lines(directions: &[Move; 4]) -> [Bitboard; 64] {
+    let mut lines = [0; 64];
+    let mut position = 0;
+
+    while position < 64 {
+        lines[position] = ray_attacks(position, 0, directions);
+        position += 1;
+    }
+
+    lines
+}
+
+const fn generate_between() -> [[Bitboard; 64]; 64] {
+    let mut between = [[0; 64]; 64];
+    let mut position = 0;
+
+    while position < 64 {
+        let mut direction = 0;
+
+        while direction < ALL_DIRECTION_MOVES.len() {
+            let mut passed = 0;
+            let mut current = offset_square(position, ALL_DIRECTION_MOVES[direction]);
+
+            while let Some(target) = current {
+                between[position][target] = passed;
+                passed |= square_bit(target);
+                current = offset_square(target, ALL_DIRECTION_MOVES[direction]);
+            }
+            direction += 1;
+        }
+        position += 1;
+    }
+
+    between
+}
+
+const fn generate_line() -> [[Bitboard; 64]; 64] {
+    let mut line = [[0; 64]; 64];
+    let mut position = 0;
+
+    while position < 64 {
+        let mut direction = 0;
+
+        while direction < ALL_DIRECTION_MOVES.len() {
+            let forward = ALL_DIRECTION_MOVES[direction];
+            let backward = Move {
+                dx: -forward.dx,
+                dy: -forward.dy,
+            };
+            let full_line = square_bit(position)
+                | ray_attacks(position, 0, &[forward, backward, forward, backward]);
+
+            let mut current = offset_square(position, forward);
+
+            while let Some(target) = current {
+                line[position][target] = full_line;
+                current = offset_square(target, forward);
+            }
+            direction += 1;
+        }
+        position += 1;
+    }
+
+    line
+}
+
+/*
+ * Slide along each direction until the first occupied square, which is included as it can be captured.
+ * Too slow for move generation, it is used to fill the magic tables.
+ */
+pub const fn ray_attacks(position: usize, occupied: Bitboard, directions: &[Move; 4]) -> Bitboard {
+    let mut attacks = 0;
+    let mut direction = 0;
+
+    while direction < 4 {
+        let mut current = offset_square(position, directions[direction]);
+
+        while let Some(target) = current {
+            attacks |= square_bit(target);
+
+            if occupied & square_bit(target) != 0 {
+                break;
+            }
+            current = offset_square(target, directions[direction]);
+        }
+        direction += 1;
+    }
+
+    attacks
+}
+
+pub fn bishop_ray_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    ray_attacks(position, occupied, &DIAGONAL_MOVES)
+}
+
+pub fn rook_ray_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    ray_attacks(position, occupied, &ANTI_DIAGONAL_MOVES)
+}
+
+pub fn bishop_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    bishop_magic_attacks(position, occupied)
+}
+
+pub fn rook_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    rook_magic_attacks(position, occupied)
+}
+
+pub fn queen_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    bishop_attacks(position, occupied) | rook_attacks(position, occupied)
+}
@@ -1,10 +1,19 @@
 use crate::{
+    bitboard::Bitboard,
     chessboard::ChessBoard,
-    moves::{DIAGONAL_MOVES, LegalMove, MoveType},
+    moves::{LegalMove, MoveType},
 };
 
-use super::get_multi_step_pseudo_legal_moves;
+use super::attacks::bishop_attacks;
+use super::push_moves_to_targets;
 
-pub fn get_bishop_moves(position: usize, chessboard: &ChessBoard) -> Vec<LegalMove> {
-    get_multi_step_pseudo_legal_moves(chessboard, &DIAGONAL_MOVES, position, MoveType::Normal)
+pub fn get_bishop_moves(
+    position: usize,
+    chessboard: &ChessBoard,
+    allowed: Bitboard,
+    moves: &mut Vec<LegalMove>,
+) {
+    let attacks = bishop_attacks(position, chessboard.bitboards.occupied) & allowed;
+
+    push_moves_to_targets(chessboard, position, attacks, MoveType::Normal, moves);
 }
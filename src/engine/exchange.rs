@@ -0,0 +1,101 @@
+use crate::bitboard::square_bit;
+use crate::chessboard::{ChessBoard, EMPTY, WKING, WPAWN};
+use crate::moves::{LegalMove, MoveType};
+
+use super::{attackers_to, opponent};
+
+/* Piece values for static exchange evaluation and move ordering, indexed by piece type WPAWN - 1 up to WKING - 1. */
+pub const EXCHANGE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 20_000];
+
+/* The longest capture sequence on one square: every piece on the board takes once. */
+const MAX_EXCHANGES: usize = 32;
+
+/* The value of a piece of either colour, 0 for EMPTY. */
+pub fn piece_value(piece: i8) -> i32 {
+    if piece == EMPTY {
+        0
+    } else {
+        EXCHANGE_VALUES[(piece.unsigned_abs() - 1) as usize]
+    }
+}
+
+/* What a promotion adds to the material of the side promoting, 0 for other moves. */
+pub fn promotion_value(legal_move: &LegalMove) -> i32 {
+    match legal_move.move_type {
+        MoveType::PawnMove {
+            promotion_move: Some(piece),
+        } => piece_value(piece) - piece_value(WPAWN),
+        _ => 0,
+    }
+}
+
+/* The piece the move takes, the pawn beside the target square for en passant, EMPTY if it takes nothing. */
+pub fn captured_piece(chessboard: &ChessBoard, legal_move: &LegalMove) -> i8 {
+    match legal_move.move_type {
+        MoveType::Enpassant { target_square } => chessboard.board[target_square],
+        _ => chessboard.board[legal_move.to],
+    }
+}
+
+/*
+ * The material the side to move wins with the move when both sides keep recapturing on its square with their
+ * least valuable piece, and either side may stop when going on would lose. Sliders behind a capturing piece join
+ * in as it leaves. Pins are not taken into account.
+ */
+pub fn static_exchange(chessboard: &ChessBoard, legal_move: LegalMove) -> i32 {
+    let bitboards = &chessboard.bitboards;
+    let to = legal_move.to;
+
+    let mut occupied = bitboards.occupied ^ square_bit(legal_move.from);
+
+    if let MoveType::Enpassant { target_square } = legal_move.move_type {
+        occupied ^= square_bit(target_square);
+    }
+
+    let mut gains = [0; MAX_EXCHANGES];
+    gains[0] = piece_value(captured_piece(chessboard, &legal_move)) + promotion_value(&legal_move);
+
+    let mut piece_on_square =
+        piece_value(chessboard.board[legal_move.from]) + promotion_value(&legal_move);
+    let mut side = opponent(chessboard.side_to_move);
+    let mut exchanges = 0;
+
+    while exchanges + 1 < MAX_EXCHANGES {
+        let attackers = attackers_to(bitboards, to, occupied) & occupied & bitboards.colour(side);
+
+        let Some((piece_type, square)) = (WPAWN..=WKING).find_map(|piece_type| {
+            let pieces = attackers & bitboards.pieces(side, piece_type);
+
+            (pieces != 0).then(|| (piece_type, pieces.trailing_zeros() as usize))
+        }) else {
+            break;
+        };
+
+        // The king may only take last, when nothing defends the square anymore.
+        if piece_type == WKING {
+            let remaining = occupied ^ square_bit(square);
+            let defenders = attackers_to(bitboards, to, remaining)
+                & remaining
+                & bitboards.colour(opponent(side));
+
+            if defenders != 0 {
+                break;
+            }
+        }
+
+        exchanges += 1;
+        gains[exchanges] = piece_on_square - gains[exchanges - 1];
+
+        piece_on_square = piece_value(piece_type);
+        occupied ^= square_bit(square);
+        side = opponent(side);
+    }
+
+    // Going back through the exchange, each side only recaptures if that does not lose compared to stopping.
+    while exchanges > 0 {
+        gains[exchanges - 1] = -(-gains[exchanges - 1]).max(gains[exchanges]);
+        exchanges -= 1;
+    }
+
+    gains[0]
+}
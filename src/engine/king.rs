@@ -1,53 +1,91 @@
 use crate::{
-    chessboard::{ChessBoard, EMPTY, Players},
-    moves::{ALL_DIRECTION_MOVES, LegalMove, MoveType},
+    bitboard::{Bitboard, square_bit},
+    chessboard::{ChessBoard, WQUEEN, WROOK, back_rank, castling_index},
+    moves::{LegalMove, MoveType},
 };
 
-use super::single_step_get_pseudo_legal_moves;
+use super::attacks::{BETWEEN, KING_ATTACKS, rook_attacks};
+use super::{opponent, push_moves_to_targets};
 
-pub fn get_king_moves(position: usize, chessboard: &ChessBoard) -> Vec<LegalMove> {
-    let mut moves = single_step_get_pseudo_legal_moves(
+/*
+ * The king may go to any square the opponent does not attack, and castle when neither its own square nor the squares
+ * it passes are attacked.
+ */
+pub fn get_king_moves(
+    position: usize,
+    chessboard: &ChessBoard,
+    enemy_attacks: Bitboard,
+    moves: &mut Vec<LegalMove>,
+) {
+    push_moves_to_targets(
         chessboard,
-        &ALL_DIRECTION_MOVES,
         position,
+        KING_ATTACKS[position] & !enemy_attacks,
         MoveType::KingMove,
+        moves,
     );
 
-    let (king_ability, queen_ability) = if chessboard.side_to_move == Players::White {
-        (
-            chessboard.castling_ability[0],
-            chessboard.castling_ability[1],
-        )
+    if enemy_attacks & square_bit(position) != 0 {
+        return;
+    }
+
+    for (king_side, move_type) in [
+        (true, MoveType::CastleKingSide),
+        (false, MoveType::CastleQueenSide),
+    ] {
+        if let Some(castle) = get_castling_move(position, chessboard, enemy_attacks, king_side) {
+            moves.push(LegalMove {
+                from: position,
+                to: castle,
+                move_type,
+                is_capture: false,
+            });
+        }
+    }
+}
+
+/*
+ * Castling ends with the king on the g- or c-file and the rook next to it, wherever they started in Chess960.
+ * Every square either of them passes or lands on has to be empty apart from the two of them, and the squares the
+ * king passes or lands on must not be attacked. Returns the square the king lands on.
+ */
+fn get_castling_move(
+    position: usize,
+    chessboard: &ChessBoard,
+    enemy_attacks: Bitboard,
+    king_side: bool,
+) -> Option<usize> {
+    let side = chessboard.side_to_move;
+    let rook_file = chessboard.castling_ability[castling_index(side, king_side)]?;
+
+    let back_rank = back_rank(side);
+    let rook_from = back_rank + rook_file;
+    let (king_to, rook_to) = if king_side {
+        (back_rank + 6, back_rank + 5)
     } else {
-        (
-            chessboard.castling_ability[2],
-            chessboard.castling_ability[3],
-        )
+        (back_rank + 2, back_rank + 3)
     };
-    if king_ability
-        && chessboard.board[position + 1] == EMPTY
-        && chessboard.board[position + 2] == EMPTY
+
+    let king_path = BETWEEN[position][king_to] | square_bit(king_to);
+    let rook_path = BETWEEN[rook_from][rook_to] | square_bit(rook_to);
+    let castling_pieces = square_bit(position) | square_bit(rook_from);
+
+    let occupied = chessboard.bitboards.occupied;
+
+    if (king_path | rook_path) & occupied & !castling_pieces != 0 || king_path & enemy_attacks != 0
     {
-        moves.push(LegalMove {
-            from: position,
-            to: position + 2,
-            move_type: MoveType::CastleKingSide,
-            is_capture: false,
-        });
+        return None;
     }
 
-    if queen_ability
-        && chessboard.board[position - 1] == EMPTY
-        && chessboard.board[position - 2] == EMPTY
-        && chessboard.board[position - 3] == EMPTY
-    {
-        moves.push(LegalMove {
-            from: position,
-            to: position - 2,
-            move_type: MoveType::CastleQueenSide,
-            is_capture: false,
-        });
+    // The rook may have shielded the king's new square from a rook or queen further along the back rank.
+    let occupied_after = (occupied & !castling_pieces) | square_bit(king_to) | square_bit(rook_to);
+    let opponent = opponent(side);
+    let rank_attackers = chessboard.bitboards.pieces(opponent, WROOK)
+        | chessboard.bitboards.pieces(opponent, WQUEEN);
+
+    if rook_attacks(king_to, occupied_after) & rank_attackers != 0 {
+        return None;
     }
 
-    moves
+    Some(king_to)
 }
@@ -1,10 +1,23 @@
 use crate::{
+    bitboard::Bitboard,
     chessboard::ChessBoard,
-    moves::{KNIGHT_MOVES, LegalMove, MoveType},
+    moves::{LegalMove, MoveType},
 };
 
-use super::single_step_get_pseudo_legal_moves;
+use super::attacks::KNIGHT_ATTACKS;
+use super::push_moves_to_targets;
 
-pub fn get_knight_moves(position: usize, chessboard: &ChessBoard) -> Vec<LegalMove> {
-    single_step_get_pseudo_legal_moves(chessboard, &KNIGHT_MOVES, position, MoveType::Normal)
+pub fn get_knight_moves(
+    position: usize,
+    chessboard: &ChessBoard,
+    allowed: Bitboard,
+    moves: &mut Vec<LegalMove>,
+) {
+    push_moves_to_targets(
+        chessboard,
+        position,
+        KNIGHT_ATTACKS[position] & allowed,
+        MoveType::Normal,
+        moves,
+    );
 }
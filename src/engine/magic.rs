@@ -0,0 +1,169 @@
+use std::sync::OnceLock;
+
+use crate::bitboard::Bitboard;
+use crate::chessboard::Move;
+use crate::moves::{ANTI_DIAGONAL_MOVES, DIAGONAL_MOVES};
+
+use super::attacks::ray_attacks;
+
+/*
+ * Magic bitboards: the blockers on the relevant squares of a slider are multiplied by a magic number, whose top bits
+ * then index a table holding the attacks for exactly that set of blockers. The table is filled on first use.
+ */
+#[derive(Debug, Clone, Copy)]
+struct Magic {
+    mask: Bitboard, /* The squares whose occupancy changes the attacks, the board edges excluded */
+    magic: u64,
+    shift: u32,
+    offset: usize, /* Where the attacks of this square start in MAGIC_ATTACKS */
+}
+
+impl Magic {
+    const fn index(&self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+const FILE_H: Bitboard = FILE_A << 7;
+const RANK_1: Bitboard = 0xff;
+const RANK_8: Bitboard = RANK_1 << 56;
+
+/* The sum of 2^(relevant squares) over all squares, 5248 entries for bishops and 102400 for rooks. */
+const BISHOP_TABLE_SIZE: usize = 5248;
+const ROOK_TABLE_SIZE: usize = 102400;
+
+const BISHOP_MAGICS: [Magic; 64] = build_magics(&BISHOP_MAGIC_NUMBERS, &DIAGONAL_MOVES, 0);
+const ROOK_MAGICS: [Magic; 64] =
+    build_magics(&ROOK_MAGIC_NUMBERS, &ANTI_DIAGONAL_MOVES, BISHOP_TABLE_SIZE);
+
+static MAGIC_ATTACKS: OnceLock<Vec<Bitboard>> = OnceLock::new();
+
+fn magic_attacks() -> &'static [Bitboard] {
+    MAGIC_ATTACKS.get_or_init(build_attack_table)
+}
+
+pub fn bishop_magic_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    magic_attacks()[BISHOP_MAGICS[position].index(occupied)]
+}
+
+pub fn rook_magic_attacks(position: usize, occupied: Bitboard) -> Bitboard {
+    magic_attacks()[ROOK_MAGICS[position].index(occupied)]
+}
+
+const fn edges(position: usize) -> Bitboard {
+    let rank = RANK_1 << (position / 8 * 8);
+    let file = FILE_A << (position % 8);
+
+    ((RANK_1 | RANK_8) & !rank) | ((FILE_A | FILE_H) & !file)
+}
+
+const fn build_magics(
+    magic_numbers: &[u64; 64],
+    directions: &[Move; 4],
+    start: usize,
+) -> [Magic; 64] {
+    let mut magics = [Magic {
+        mask: 0,
+        magic: 0,
+        shift: 0,
+        offset: 0,
+    }; 64];
+
+    let mut offset = start;
+    let mut position = 0;
+
+    while position < 64 {
+        let mask = ray_attacks(position, 0, directions) & !edges(position);
+        let bits = mask.count_ones();
+
+        magics[position] = Magic {
+            mask,
+            magic: magic_numbers[position],
+            shift: 64 - bits,
+            offset,
+        };
+
+        offset += 1 << bits;
+        position += 1;
+    }
+
+    magics
+}
+
+/*
+ * Walk every subset of blockers of every square, with the carry-rippler trick, and store its attacks.
+ */
+fn build_attack_table() -> Vec<Bitboard> {
+    let mut attacks = vec![0; BISHOP_TABLE_SIZE + ROOK_TABLE_SIZE];
+
+    for (magics, directions) in [
+        (&BISHOP_MAGICS, &DIAGONAL_MOVES),
+        (&ROOK_MAGICS, &ANTI_DIAGONAL_MOVES),
+    ] {
+        for (position, magic) in magics.iter().enumerate() {
+            let mut blockers: Bitboard = 0;
+
+            loop {
+                let index = magic.index(blockers);
+                let attack = ray_attacks(position, blockers, directions);
+
+                // The ray attacks always include at least one square, so 0 marks an unused entry.
+                assert!(
+                    attacks[index] == 0 || attacks[index] == attack,
+                    "Invalid magic number for square {position}"
+                );
+                attacks[index] = attack;
+
+                blockers = blockers.wrapping_sub(magic.mask) & magic.mask;
+
+                if blockers == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    attacks
+}
+
+/* Found with a seeded random search over sparse numbers, i.e. the bitwise and of three random numbers. */
+#[rustfmt::skip]
+const BISHOP_MAGIC_NUMBERS: [u64; 64] = [
+    0x0020_4284_0040_8200, 0x2008_0101_0421_0004, 0x02d0_0092_0048_0190, 0x0018_158b_0001_0100,
+    0x02c4_0421_3204_8008, 0x0200_8220_2000_c221, 0x4000_4210_5008_0009, 0x0210_1402_0202_2020,
+    0x00c0_1014_1004_2248, 0x0405_2048_00d4_8080, 0x3800_c892_0042_0002, 0x1808_4412_4a02_0440,
+    0x0440_3410_a800_2221, 0x4040_2090_0420_0400, 0x0840_0402_0202_a204, 0x3010_0021_0402_2000,
+    0x0020_0240_a911_0900, 0x2302_8004_0408_0210, 0x0204_1888_0024_0010, 0x8048_000c_0140_1200,
+    0x120c_001a_1104_0900, 0x0000_4012_0050_0440, 0x0000_4040_8404_20a0, 0x0020_9308_2288_0804,
+    0x4044_4010_9090_0161, 0x0034_1000_1521_0804, 0x8004_1000_0901_0120, 0x48c8_0800_0082_0500,
+    0x0080_8480_0400_2000, 0x0801_0040_1200_5044, 0x0000_8090_2c04_0400, 0x0004_0090_0500_4100,
+    0x0b10_3010_048a_0200, 0x8004_1002_0318_1a00, 0x0800_1402_0010_0080, 0x8401_0108_0091_0040,
+    0x0840_0100_1129_0040, 0x4010_0214_202e_1000, 0x0842_0400_4001_0840, 0x0028_0100_4001_0860,
+    0x0008_0202_a205_1000, 0x4200_8410_0808_4204, 0x0021_1201_1000_0d02, 0x48c1_0042_0800_0084,
+    0x0010_0881_0041_4400, 0x0021_1010_0042_0580, 0x0010_0405_5840_1410, 0x200c_0c82_a105_0205,
+    0x0011_1088_2008_8000, 0x0001_0119_1012_0402, 0x1580_0086_0809_1248, 0x8010_0180_2088_0c02,
+    0x20a1_1010_3208_8480, 0x0080_1004_0808_2800, 0x2810_0401_1404_01c0, 0x8002_1022_0093_0012,
+    0x4001_0400_8208_0200, 0x0822_00a4_9808_1808, 0x0005_0861_0080_d003, 0x0052_0200_4484_2402,
+    0x4800_a001_40c8_4840, 0x5000_0008_4808_0820, 0x0101_0860_0424_0040, 0x0028_2808_0800_5014,
+];
+
+#[rustfmt::skip]
+const ROOK_MAGIC_NUMBERS: [u64; 64] = [
+    0x0080_0090_8064_c000, 0x0040_2000_4000_1000, 0x0180_1000_80a0_010a, 0x8880_0410_0080_0800,
+    0x1200_1002_0120_0804, 0x0200_0200_0401_1008, 0x2180_0100_0080_0600, 0x0200_0050_8821_0204,
+    0x0400_8000_4000_8021, 0x0400_4000_2000_5000, 0x8240_8010_0020_0080, 0x8611_0010_0420_0900,
+    0x0081_8080_0c00_1800, 0x0100_8002_0080_0400, 0x0a02_0001_0200_0408, 0x8020_8023_0010_4280,
+    0x0080_0040_0040_2000, 0xe010_1040_0040_2000, 0x0800_8080_1000_2000, 0xa280_2100_0810_0100,
+    0x0001_8180_1400_0800, 0xa002_0101_0008_0400, 0x0080_2400_0102_0870, 0x0001_0200_0404_8845,
+    0x0081_8262_8000_4004, 0x2020_8109_0028_4000, 0x0200_1000_8080_2000, 0x0200_0800_8010_0080,
+    0x8083_0801_0010_0500, 0x4406_0009_0100_0400, 0x0005_0200_8080_0100, 0x0090_2042_0000_8114,
+    0x0010_4000_9480_0420, 0x0900_8040_0080_2002, 0x0201_0018_4100_2000, 0x4100_0800_8080_1000,
+    0x4540_0400_8080_0800, 0x0002_0010_0404_0020, 0x0281_1958_1400_1002, 0x1240_8000_4080_0100,
+    0x0880_0420_0052_4004, 0x02c0_8041_0206_002c, 0x0801_2002_4105_0010, 0x8400_0800_1000_8080,
+    0x0008_0005_0009_0010, 0x0082_0090_8402_0008, 0x4012_0001_0802_0004, 0x9000_104d_0886_0004,
+    0x2004_2041_1480_0100, 0x0148_8021_1240_0300, 0x0202_8420_0010_0880, 0x001b_0800_8090_0080,
+    0x001a_0020_0810_0600, 0x0004_0080_0402_0080, 0x5181_0006_0004_0300, 0x0000_0444_0112_8a00,
+    0x8044_1104_8000_2441, 0x2008_1100_8440_2202, 0x9080_6005_0900_10c1, 0x0004_2031_0a00_4a42,
+    0x0023_0010_0402_0801, 0x0882_0010_0804_0102, 0x0002_3008_8118_020c, 0x0000_0190_2504_0042,
+];
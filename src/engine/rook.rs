@@ -1,15 +1,19 @@
 use crate::{
+    bitboard::Bitboard,
     chessboard::ChessBoard,
-    moves::{ANTI_DIAGONAL_MOVES, LegalMove, MoveType},
+    moves::{LegalMove, MoveType},
 };
 
-use super::get_multi_step_pseudo_legal_moves;
+use super::attacks::rook_attacks;
+use super::push_moves_to_targets;
 
-pub fn get_rook_moves(position: usize, chessboard: &ChessBoard) -> Vec<LegalMove> {
-    get_multi_step_pseudo_legal_moves(
-        chessboard,
-        &ANTI_DIAGONAL_MOVES,
-        position,
-        MoveType::RookMove,
-    )
+pub fn get_rook_moves(
+    position: usize,
+    chessboard: &ChessBoard,
+    allowed: Bitboard,
+    moves: &mut Vec<LegalMove>,
+) {
+    let attacks = rook_attacks(position, chessboard.bitboards.occupied) & allowed;
+
+    push_moves_to_targets(chessboard, position, attacks, MoveType::RookMove, moves);
 }
@@ -0,0 +1,490 @@
+use crate::chessboard::{
+    BPAWN, ChessBoard, EMPTY, Move, Players, WBISHOP, WKING, WKNIGHT, WPAWN, WQUEEN, WROOK,
+};
+use crate::moves::{ALL_DIRECTION_MOVES, ANTI_DIAGONAL_MOVES, DIAGONAL_MOVES, KNIGHT_MOVES};
+
+/*
+ * A pair of values for the middlegame and the endgame, blended by the game phase.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PhaseScore {
+    pub middlegame: i32,
+    pub endgame: i32,
+}
+
+impl PhaseScore {
+    pub const fn new(middlegame: i32, endgame: i32) -> Self {
+        Self {
+            middlegame,
+            endgame,
+        }
+    }
+
+    fn add(&mut self, other: PhaseScore, times: i32) {
+        self.middlegame += other.middlegame * times;
+        self.endgame += other.endgame * times;
+    }
+}
+
+/* Phase contribution of knights, bishops, rooks and queens. A full board has phase 24. */
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+
+/*
+ * Every weight of the evaluation, so they can be tuned. Arrays indexed by piece go pawn, knight, bishop, rook,
+ * queen, king, i.e. WPAWN - 1 up to WKING - 1. Piece-square tables are written from white's point of view with a8
+ * as the first entry, so they read like a board diagram.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationWeights {
+    pub piece_values: [PhaseScore; 6],
+    pub middlegame_tables: [[i32; 64]; 6],
+    pub endgame_tables: [[i32; 64]; 6],
+    pub doubled_pawn: PhaseScore,
+    pub isolated_pawn: PhaseScore,
+    pub passed_pawn: [PhaseScore; 8], /* Indexed by the rank of the pawn as seen from its own side */
+    pub mobility: [PhaseScore; 6],    /* Per reachable square above mobility_baseline */
+    pub mobility_baseline: [i32; 6],
+    pub king_pawn_shield: PhaseScore, /* Per own pawn on the three files around the king, one or two ranks ahead */
+    pub king_open_file: PhaseScore,   /* Per file around the king without an own pawn */
+}
+
+impl Default for EvaluationWeights {
+    fn default() -> Self {
+        DEFAULT_WEIGHTS
+    }
+}
+
+/*
+ * Evaluate the position in centipawns from the point of view of the side to move.
+ */
+pub fn evaluate(chessboard: &ChessBoard) -> i32 {
+    evaluate_with_weights(chessboard, &DEFAULT_WEIGHTS)
+}
+
+pub fn evaluate_with_weights(chessboard: &ChessBoard, weights: &EvaluationWeights) -> i32 {
+    let white = evaluate_side(chessboard, weights, Players::White);
+    let black = evaluate_side(chessboard, weights, Players::Black);
+
+    let phase = game_phase(&chessboard.board);
+
+    let middlegame = white.middlegame - black.middlegame;
+    let endgame = white.endgame - black.endgame;
+
+    let white_score = (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+
+    match chessboard.side_to_move {
+        Players::White => white_score,
+        Players::Black => -white_score,
+    }
+}
+
+/*
+ * The phase goes from MAX_PHASE with all pieces on the board down to 0 with only kings and pawns.
+ */
+pub fn game_phase(board: &[i8; 64]) -> i32 {
+    let phase: i32 = board
+        .iter()
+        .filter(|piece| **piece != EMPTY)
+        .map(|piece| PHASE_WEIGHTS[piece_index(*piece)])
+        .sum();
+
+    phase.min(MAX_PHASE)
+}
+
+fn piece_index(piece: i8) -> usize {
+    (piece.unsigned_abs() - 1) as usize
+}
+
+fn is_own_piece(piece: i8, side: Players) -> bool {
+    match side {
+        Players::White => piece.is_positive(),
+        Players::Black => piece.is_negative(),
+    }
+}
+
+/*
+ * Map a square to the index of the piece-square tables, which are written from white's point of view.
+ */
+fn table_index(position: usize, side: Players) -> usize {
+    match side {
+        Players::White => position ^ 56,
+        Players::Black => position,
+    }
+}
+
+/* The rank of a square as seen from the given side, 0 being its back rank. */
+fn relative_rank(position: usize, side: Players) -> usize {
+    match side {
+        Players::White => position / 8,
+        Players::Black => 7 - position / 8,
+    }
+}
+
+fn evaluate_side(
+    chessboard: &ChessBoard,
+    weights: &EvaluationWeights,
+    side: Players,
+) -> PhaseScore {
+    let board = &chessboard.board;
+    let mut score = PhaseScore::default();
+
+    for (position, piece) in board.iter().enumerate() {
+        if *piece == EMPTY || !is_own_piece(*piece, side) {
+            continue;
+        }
+
+        let index = piece_index(*piece);
+        let table_position = table_index(position, side);
+
+        score.add(weights.piece_values[index], 1);
+        score.middlegame += weights.middlegame_tables[index][table_position];
+        score.endgame += weights.endgame_tables[index][table_position];
+
+        let mobility = match piece.abs() {
+            WKNIGHT => count_reachable_squares(board, position, &KNIGHT_MOVES, side, false),
+            WBISHOP => count_reachable_squares(board, position, &DIAGONAL_MOVES, side, true),
+            WROOK => count_reachable_squares(board, position, &ANTI_DIAGONAL_MOVES, side, true),
+            WQUEEN => count_reachable_squares(board, position, &ALL_DIRECTION_MOVES, side, true),
+            _ => continue,
+        };
+
+        score.add(
+            weights.mobility[index],
+            mobility - weights.mobility_baseline[index],
+        );
+    }
+
+    evaluate_pawn_structure(board, weights, side, &mut score);
+    evaluate_king_safety(chessboard, weights, side, &mut score);
+
+    score
+}
+
+/*
+ * Count the empty or enemy-occupied squares a piece attacks, sliding pieces continue until they hit a piece.
+ */
+fn count_reachable_squares(
+    board: &[i8; 64],
+    position: usize,
+    directions: &[Move],
+    side: Players,
+    is_sliding: bool,
+) -> i32 {
+    let mut count = 0;
+
+    for direction in directions {
+        let mut position_opt = direction.get_new_position(position);
+
+        while let Some(new_position) = position_opt {
+            let square = board[new_position];
+
+            if square != EMPTY {
+                if !is_own_piece(square, side) {
+                    count += 1;
+                }
+                break;
+            }
+
+            count += 1;
+
+            if !is_sliding {
+                break;
+            }
+            position_opt = direction.get_new_position(new_position);
+        }
+    }
+
+    count
+}
+
+fn evaluate_pawn_structure(
+    board: &[i8; 64],
+    weights: &EvaluationWeights,
+    side: Players,
+    score: &mut PhaseScore,
+) {
+    let (own_pawn, enemy_pawn) = match side {
+        Players::White => (WPAWN, BPAWN),
+        Players::Black => (BPAWN, WPAWN),
+    };
+
+    let mut pawns_on_file = [0; 8];
+
+    for (position, piece) in board.iter().enumerate() {
+        if *piece == own_pawn {
+            pawns_on_file[position % 8] += 1;
+        }
+    }
+
+    for (file, pawns) in pawns_on_file.iter().enumerate() {
+        if *pawns > 1 {
+            score.add(weights.doubled_pawn, pawns - 1);
+        }
+
+        let has_neighbour =
+            (file > 0 && pawns_on_file[file - 1] > 0) || (file < 7 && pawns_on_file[file + 1] > 0);
+
+        if *pawns > 0 && !has_neighbour {
+            score.add(weights.isolated_pawn, *pawns);
+        }
+    }
+
+    for (position, piece) in board.iter().enumerate() {
+        if *piece != own_pawn {
+            continue;
+        }
+
+        let file = position % 8;
+        let rank = relative_rank(position, side);
+
+        // A pawn is passed when no enemy pawn in front of it, on its own or a neighbouring file, can stop it.
+        let is_passed = board.iter().enumerate().all(|(enemy_position, enemy)| {
+            *enemy != enemy_pawn
+                || (enemy_position % 8).abs_diff(file) > 1
+                || relative_rank(enemy_position, side) <= rank
+        });
+
+        if is_passed {
+            score.add(weights.passed_pawn[rank], 1);
+        }
+    }
+}
+
+fn evaluate_king_safety(
+    chessboard: &ChessBoard,
+    weights: &EvaluationWeights,
+    side: Players,
+    score: &mut PhaseScore,
+) {
+    let board = &chessboard.board;
+
+    let (king_position, own_pawn) = match side {
+        Players::White => (chessboard.white_king_position, WPAWN),
+        Players::Black => (chessboard.black_king_position, BPAWN),
+    };
+
+    debug_assert!(board[king_position].abs() == WKING);
+
+    let king_file = king_position % 8;
+    let king_rank = relative_rank(king_position, side);
+
+    for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+        let mut has_pawn = false;
+
+        for (position, piece) in board.iter().enumerate() {
+            if *piece != own_pawn || position % 8 != file {
+                continue;
+            }
+
+            has_pawn = true;
+
+            let rank = relative_rank(position, side);
+
+            if rank > king_rank && rank <= king_rank + 2 {
+                score.add(weights.king_pawn_shield, 1);
+            }
+        }
+
+        if !has_pawn {
+            score.add(weights.king_open_file, 1);
+        }
+    }
+}
+
+/*
+ * Material and piece-square tables are the PeSTO values by Ronald Friederich.
+ */
+pub const DEFAULT_WEIGHTS: EvaluationWeights = EvaluationWeights {
+    piece_values: [
+        PhaseScore::new(82, 94),
+        PhaseScore::new(337, 281),
+        PhaseScore::new(365, 297),
+        PhaseScore::new(477, 512),
+        PhaseScore::new(1025, 936),
+        PhaseScore::new(0, 0),
+    ],
+    middlegame_tables: [
+        MIDDLEGAME_PAWN_TABLE,
+        MIDDLEGAME_KNIGHT_TABLE,
+        MIDDLEGAME_BISHOP_TABLE,
+        MIDDLEGAME_ROOK_TABLE,
+        MIDDLEGAME_QUEEN_TABLE,
+        MIDDLEGAME_KING_TABLE,
+    ],
+    endgame_tables: [
+        ENDGAME_PAWN_TABLE,
+        ENDGAME_KNIGHT_TABLE,
+        ENDGAME_BISHOP_TABLE,
+        ENDGAME_ROOK_TABLE,
+        ENDGAME_QUEEN_TABLE,
+        ENDGAME_KING_TABLE,
+    ],
+    doubled_pawn: PhaseScore::new(-10, -20),
+    isolated_pawn: PhaseScore::new(-10, -15),
+    passed_pawn: [
+        PhaseScore::new(0, 0),
+        PhaseScore::new(5, 10),
+        PhaseScore::new(10, 15),
+        PhaseScore::new(15, 25),
+        PhaseScore::new(25, 45),
+        PhaseScore::new(40, 75),
+        PhaseScore::new(60, 110),
+        PhaseScore::new(0, 0),
+    ],
+    mobility: [
+        PhaseScore::new(0, 0),
+        PhaseScore::new(4, 4),
+        PhaseScore::new(5, 5),
+        PhaseScore::new(2, 4),
+        PhaseScore::new(1, 2),
+        PhaseScore::new(0, 0),
+    ],
+    mobility_baseline: [0, 4, 7, 7, 14, 0],
+    king_pawn_shield: PhaseScore::new(10, 0),
+    king_open_file: PhaseScore::new(-20, 0),
+};
+
+#[rustfmt::skip]
+const MIDDLEGAME_PAWN_TABLE: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     98, 134,  61,  95,  68, 126,  34, -11,
+     -6,   7,  26,  31,  65,  56,  25, -20,
+    -14,  13,   6,  21,  23,  12,  17, -23,
+    -27,  -2,  -5,  12,  17,   6,  10, -25,
+    -26,  -4,  -4, -10,   3,   3,  33, -12,
+    -35,  -1, -20, -23, -15,  24,  38, -22,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const ENDGAME_PAWN_TABLE: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+    178, 173, 158, 134, 147, 132, 165, 187,
+     94, 100,  85,  67,  56,  53,  82,  84,
+     32,  24,  13,   5,  -2,   4,  17,  17,
+     13,   9,  -3,  -7,  -7,  -8,   3,  -1,
+      4,   7,  -6,   1,   0,  -5,  -1,  -8,
+     13,   8,   8,  10,  13,   0,   2,  -7,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const MIDDLEGAME_KNIGHT_TABLE: [i32; 64] = [
+    -167, -89, -34, -49,  61, -97, -15, -107,
+     -73, -41,  72,  36,  23,  62,   7,  -17,
+     -47,  60,  37,  65,  84, 129,  73,   44,
+      -9,  17,  19,  53,  37,  69,  18,   22,
+     -13,   4,  16,  13,  28,  19,  21,   -8,
+     -23,  -9,  12,  10,  19,  17,  25,  -16,
+     -29, -53, -12,  -3,  -1,  18, -14,  -19,
+    -105, -21, -58, -33, -17, -28, -19,  -23,
+];
+
+#[rustfmt::skip]
+const ENDGAME_KNIGHT_TABLE: [i32; 64] = [
+    -58, -38, -13, -28, -31, -27, -63, -99,
+    -25,  -8, -25,  -2,  -9, -25, -24, -52,
+    -24, -20,  10,   9,  -1,  -9, -19, -41,
+    -17,   3,  22,  22,  22,  11,   8, -18,
+    -18,  -6,  16,  25,  16,  17,   4, -18,
+    -23,  -3,  -1,  15,  10,  -3, -20, -22,
+    -42, -20, -10,  -5,  -2, -20, -23, -44,
+    -29, -51, -23, -15, -22, -18, -50, -64,
+];
+
+#[rustfmt::skip]
+const MIDDLEGAME_BISHOP_TABLE: [i32; 64] = [
+    -29,   4, -82, -37, -25, -42,   7,  -8,
+    -26,  16, -18, -13,  30,  59,  18, -47,
+    -16,  37,  43,  40,  35,  50,  37,  -2,
+     -4,   5,  19,  50,  37,  37,   7,  -2,
+     -6,  13,  13,  26,  34,  12,  10,   4,
+      0,  15,  15,  15,  14,  27,  18,  10,
+      4,  15,  16,   0,   7,  21,  33,   1,
+    -33,  -3, -14, -21, -13, -12, -39, -21,
+];
+
+#[rustfmt::skip]
+const ENDGAME_BISHOP_TABLE: [i32; 64] = [
+    -14, -21, -11,  -8,  -7,  -9, -17, -24,
+     -8,  -4,   7, -12,  -3, -13,  -4, -14,
+      2,  -8,   0,  -1,  -2,   6,   0,   4,
+     -3,   9,  12,   9,  14,  10,   3,   2,
+     -6,   3,  13,  19,   7,  10,  -3,  -9,
+    -12,  -3,   8,  10,  13,   3,  -7, -15,
+    -14, -18,  -7,  -1,   4,  -9, -15, -27,
+    -23,  -9, -23,  -5,  -9, -16,  -5, -17,
+];
+
+#[rustfmt::skip]
+const MIDDLEGAME_ROOK_TABLE: [i32; 64] = [
+     32,  42,  32,  51,  63,   9,  31,  43,
+     27,  32,  58,  62,  80,  67,  26,  44,
+     -5,  19,  26,  36,  17,  45,  61,  16,
+    -24, -11,   7,  26,  24,  35,  -8, -20,
+    -36, -26, -12,  -1,   9,  -7,   6, -23,
+    -45, -25, -16, -17,   3,   0,  -5, -33,
+    -44, -16, -20,  -9,  -1,  11,  -6, -71,
+    -19, -13,   1,  17,  16,   7, -37, -26,
+];
+
+#[rustfmt::skip]
+const ENDGAME_ROOK_TABLE: [i32; 64] = [
+     13,  10,  18,  15,  12,  12,   8,   5,
+     11,  13,  13,  11,  -3,   3,   8,   3,
+      7,   7,   7,   5,   4,  -3,  -5,  -3,
+      4,   3,  13,   1,   2,   1,  -1,   2,
+      3,   5,   8,   4,  -5,  -6,  -8, -11,
+     -4,   0,  -5,  -1,  -7, -12,  -8, -16,
+     -6,  -6,   0,   2,  -9,  -9, -11,  -3,
+     -9,   2,   3,  -1,  -5, -13,   4, -20,
+];
+
+#[rustfmt::skip]
+const MIDDLEGAME_QUEEN_TABLE: [i32; 64] = [
+    -28,   0,  29,  12,  59,  44,  43,  45,
+    -24, -39,  -5,   1, -16,  57,  28,  54,
+    -13, -17,   7,   8,  29,  56,  47,  57,
+    -27, -27, -16, -16,  -1,  17,  -2,   1,
+     -9, -26,  -9, -10,  -2,  -4,   3,  -3,
+    -14,   2, -11,  -2,  -5,   2,  14,   5,
+    -35,  -8,  11,   2,   8,  15,  -3,   1,
+     -1, -18,  -9,  10, -15, -25, -31, -50,
+];
+
+#[rustfmt::skip]
+const ENDGAME_QUEEN_TABLE: [i32; 64] = [
+     -9,  22,  22,  27,  27,  19,  10,  20,
+    -17,  20,  32,  41,  58,  25,  30,   0,
+    -20,   6,   9,  49,  47,  35,  19,   9,
+      3,  22,  24,  45,  57,  40,  57,  36,
+    -18,  28,  19,  47,  31,  34,  39,  23,
+    -16, -27,  15,   6,   9,  17,  10,   5,
+    -22, -23, -30, -16, -16, -23, -36, -32,
+    -33, -28, -22, -43,  -5, -32, -20, -41,
+];
+
+#[rustfmt::skip]
+const MIDDLEGAME_KING_TABLE: [i32; 64] = [
+    -65,  23,  16, -15, -56, -34,   2,  13,
+     29,  -1, -20,  -7,  -8,  -4, -38, -29,
+     -9,  24,   2, -16, -20,   6,  22, -22,
+    -17, -20, -12, -27, -30, -25, -14, -36,
+    -49,  -1, -27, -39, -46, -44, -33, -51,
+    -14, -14, -22, -46, -44, -30, -15, -27,
+      1,   7,  -8, -64, -43, -16,   9,   8,
+    -15,  36,  12, -54,   8, -28,  24,  14,
+];
+
+#[rustfmt::skip]
+const ENDGAME_KING_TABLE: [i32; 64] = [
+    -74, -35, -18, -18, -11,  15,   4, -17,
+    -12,  17,  14,  17,  17,  38,  23,  11,
+     10,  17,  23,  15,  20,  45,  44,  13,
+     -8,  22,  24,  27,  26,  33,  26,   3,
+    -18,  -4,  21,  24,  27,  23,   9, -11,
+    -19,  -3,  11,  21,  23,  16,   7,  -9,
+    -27, -11,   4,  13,  14,   4,  -5, -17,
+    -53, -34, -21, -11, -28, -14, -24, -43,
+];
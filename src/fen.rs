@@ -8,6 +8,7 @@ use std::fmt::Formatter;
 
 pub mod parsing;
 mod validation;
+pub mod writing;
 
 #[derive(Debug, PartialEq)]
 pub enum FenType {
@@ -31,6 +32,7 @@ pub enum FenErrorKind {
     MissingArgument(FenArguments),
     TooManyArguments,
     ParserError(FenArguments),
+    SideNotToMoveInCheck, /* The side to move could capture the other king */
 }
 
 #[derive(Debug, PartialEq)]
@@ -99,6 +101,8 @@ pub trait Fen {
         Ok(FenType::Full)
     }
     fn set_fen_position(fen: &str) -> Result<ChessBoard, FenError>;
+
+    fn to_fen(&self) -> String;
 }
 
 impl FenError {
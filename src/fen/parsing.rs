@@ -1,7 +1,7 @@
 use crate::{
     chessboard::{
         BBISHOP, BKING, BKNIGHT, BOARD_HEIGHT, BOARD_WIDTH, BPAWN, BQUEEN, BROOK, EMPTY, Players,
-        WBISHOP, WKING, WKNIGHT, WPAWN, WQUEEN, WROOK,
+        WBISHOP, WKING, WKNIGHT, WPAWN, WQUEEN, WROOK, back_rank, castling_index,
     },
     fen::{FenArguments, FenError, FenErrorKind},
 };
@@ -124,19 +124,41 @@ pub fn parse_side_to_move(str_part: &str) -> Option<Players> {
     }
 }
 
-pub fn parse_castling_ability(str_part: &str) -> Option<[bool; 4]> {
-    let mut castling_ability = [false; 4];
+/*
+ * Castling rights are stored as the file of the rook to castle with. K and Q stand for the outermost rook on that
+ * side of the king like in X-FEN, a file letter names the rook directly like in Shredder-FEN.
+ * A right without a king and rook on the back rank to castle with is ignored.
+ */
+pub fn parse_castling_ability(str_part: &str, board: &[i8; 64]) -> Option<[Option<usize>; 4]> {
+    let mut castling_ability = [None; 4];
+
+    if str_part == "-" {
+        return Some(castling_ability);
+    }
 
     for char in str_part.chars() {
-        match char {
-            'K' => castling_ability[0] = true,
-            'Q' => castling_ability[1] = true,
+        let (side, king, rook) = if char.is_ascii_uppercase() {
+            (Players::White, WKING, WROOK)
+        } else {
+            (Players::Black, BKING, BROOK)
+        };
+
+        let back_rank = back_rank(side);
+        let rook_files = (0..BOARD_WIDTH).filter(|file| board[back_rank + file] == rook);
+
+        let Some(king_file) = (0..BOARD_WIDTH).find(|file| board[back_rank + file] == king) else {
+            continue;
+        };
 
-            'k' => castling_ability[2] = true,
-            'q' => castling_ability[3] = true,
+        let rook_file = match char.to_ascii_lowercase() {
+            'k' => rook_files.filter(|file| *file > king_file).max(),
+            'q' => rook_files.filter(|file| *file < king_file).min(),
+            file => parse_file(file).filter(|file| board[back_rank + file] == rook),
+        };
 
-            '-' => return Some([false; 4]),
-            _ => return None,
+        if let Some(rook_file) = rook_file {
+            let king_side = rook_file > king_file;
+            castling_ability[castling_index(side, king_side)] = Some(rook_file);
         }
     }
 
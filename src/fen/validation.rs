@@ -30,10 +30,15 @@ where
     }
 }
 
+/* Eight ranks of eight squares each, with exactly one king of either colour. */
 pub fn is_position_valid(position: &str) -> bool {
     let ranks = position.split('/');
     let mut rank: u32 = 0;
 
+    if position.matches('K').count() != 1 || position.matches('k').count() != 1 {
+        return false;
+    }
+
     for rank_string in ranks {
         let mut file = 0;
 
@@ -85,41 +90,34 @@ pub fn is_side_to_move_valid(side_to_move: &str) -> bool {
     true
 }
 
+/*
+ * Accepts KQkq as well as the rook files of Shredder-FEN and X-FEN (e.g. HAha or Kd), at most two rights per side
+ * and each right once. Whether the rooks are really there is only checked when parsing the position.
+ */
 pub fn is_castling_valid(castling_ability: &str) -> bool {
-    let chars = castling_ability.chars();
-    let mut has_no_ability = false;
-    let mut char_occurences: [u32; 4] = [0; 4]; //WKing, WQueen, BKing, BQueen
-
-    for c in chars {
-        match c {
-            'K' => char_occurences[0] += 1,
-            'Q' => char_occurences[1] += 1,
-            'k' => char_occurences[2] += 1,
-            'q' => char_occurences[3] += 1,
-            '-' => {
-                if !has_no_ability {
-                    has_no_ability = true;
-                } else {
-                    return false;
-                }
-            }
-            _ => return false,
-        }
+    if castling_ability == "-" {
+        return true;
     }
-    if has_no_ability {
-        for count in char_occurences {
-            if count != 0 {
-                return false;
-            }
-        }
-    } else {
-        for count in char_occurences {
-            if !(count == 1 || count == 0) {
-                return false;
-            }
+
+    let mut seen = Vec::new();
+    let mut rights_per_side: [u32; 2] = [0; 2]; //White, Black
+
+    for c in castling_ability.chars() {
+        let side = match c {
+            'K' | 'Q' | 'A'..='H' => 0,
+            'k' | 'q' | 'a'..='h' => 1,
+            _ => return false,
+        };
+
+        if seen.contains(&c) {
+            return false;
         }
+
+        seen.push(c);
+        rights_per_side[side] += 1;
     }
-    true
+
+    !seen.is_empty() && rights_per_side.iter().all(|count| *count <= 2)
 }
 
 pub fn is_en_passant_valid(en_passant: &str) -> bool {
@@ -158,15 +156,7 @@ pub fn is_en_passant_valid(en_passant: &str) -> bool {
 }
 
 pub fn is_half_move_valid(half_move: &str) -> bool {
-    match half_move.parse::<u32>() {
-        Ok(half_move) => {
-            if half_move > 50 {
-                return false;
-            }
-        }
-        Err(_) => return false,
-    }
-    true
+    half_move.parse::<u32>().is_ok()
 }
 
 pub fn is_move_counter_valid(move_counter: &str) -> bool {
@@ -0,0 +1,96 @@
+use crate::{
+    chessboard::{
+        BOARD_HEIGHT, BOARD_WIDTH, BROOK, EMPTY, Players, WROOK, back_rank, piece_to_char,
+    },
+    moves::{file_to_char, square_to_string},
+};
+
+pub fn write_position(board: &[i8; 64]) -> String {
+    let mut position = String::with_capacity(71);
+
+    for rank in (0..BOARD_HEIGHT).rev() {
+        let mut empty_squares = 0;
+
+        for file in 0..BOARD_WIDTH {
+            let square = board[(rank * 8) + file];
+
+            if square == EMPTY {
+                empty_squares += 1;
+                continue;
+            }
+
+            if empty_squares > 0 {
+                position.push_str(&empty_squares.to_string());
+                empty_squares = 0;
+            }
+            position.push(piece_to_char(square));
+        }
+
+        if empty_squares > 0 {
+            position.push_str(&empty_squares.to_string());
+        }
+
+        if rank > 0 {
+            position.push('/');
+        }
+    }
+
+    position
+}
+
+pub fn write_side_to_move(side_to_move: Players) -> &'static str {
+    match side_to_move {
+        Players::White => "w",
+        Players::Black => "b",
+    }
+}
+
+/*
+ * Written as X-FEN: K, Q, k and q when the castling rook is the outermost rook on its side of the king, which is
+ * always the case in standard chess, and the file of the rook otherwise.
+ */
+pub fn write_castling_ability(castling_ability: &[Option<usize>; 4], board: &[i8; 64]) -> String {
+    let castling: String = ['K', 'Q', 'k', 'q']
+        .iter()
+        .zip(castling_ability)
+        .filter_map(|(char, rook_file)| {
+            let rook_file = (*rook_file)?;
+            let (side, rook) = if char.is_ascii_uppercase() {
+                (Players::White, WROOK)
+            } else {
+                (Players::Black, BROOK)
+            };
+
+            let outer_files = if char.eq_ignore_ascii_case(&'k') {
+                rook_file + 1..BOARD_WIDTH
+            } else {
+                0..rook_file
+            };
+
+            let is_outermost = outer_files
+                .into_iter()
+                .all(|file| board[back_rank(side) + file] != rook);
+
+            Some(if is_outermost {
+                *char
+            } else if char.is_ascii_uppercase() {
+                file_to_char(rook_file).to_ascii_uppercase()
+            } else {
+                file_to_char(rook_file)
+            })
+        })
+        .collect();
+
+    if castling.is_empty() {
+        String::from("-")
+    } else {
+        castling
+    }
+}
+
+pub fn write_epawn(en_passant_target_square: Option<usize>) -> String {
+    match en_passant_target_square {
+        Some(square) => square_to_string(square),
+        None => String::from("-"),
+    }
+}
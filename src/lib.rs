@@ -0,0 +1,19 @@
+pub mod bitboard;
+pub mod board_canvas;
+pub mod chess960;
+pub mod chess_game;
+pub mod chessboard;
+pub mod clock;
+pub mod engine;
+pub mod evaluation;
+pub mod fen;
+pub mod moves;
+pub mod outcome;
+pub mod perft;
+pub mod pgn;
+pub mod san;
+pub mod search;
+mod tests;
+pub mod transposition;
+pub mod uci;
+pub mod zobrist;
@@ -1,16 +1,29 @@
-mod chess_game;
-mod chessboard;
-mod engine;
-mod fen;
-mod moves;
-mod tests;
-use chess_game::ChessGame;
+use chess::chess_game::ChessGame;
+use chess::perft::{run_perft, run_perft_suite_command};
+use chess::uci::run_uci;
 
 const RUN_GAME: bool = true;
 
 fn main() {
-    if RUN_GAME {
-        let mut game = ChessGame::default();
-        _ = game.run();
+    let arguments: Vec<String> = std::env::args().collect();
+
+    match arguments.get(1).map(String::as_str) {
+        Some("uci") => run_uci(),
+        Some("perft") => {
+            if !run_perft(&arguments[2..]) {
+                std::process::exit(1);
+            }
+        }
+        Some("perft-suite") => {
+            if !run_perft_suite_command(&arguments[2..]) {
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            if RUN_GAME {
+                let mut game = ChessGame::default();
+                _ = game.run();
+            }
+        }
     }
 }
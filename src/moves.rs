@@ -1,6 +1,8 @@
+use std::fmt;
 use std::str::FromStr;
 
 use crate::chessboard::{BBISHOP, BKNIGHT, BQUEEN, BROOK, Move, WBISHOP, WKNIGHT, WQUEEN, WROOK};
+use crate::fen::parsing::{parse_file, parse_rank};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MoveType {
@@ -14,7 +16,7 @@ pub enum MoveType {
     CastleQueenSide,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LegalMove {
     pub from: usize,
     pub to: usize,
@@ -24,8 +26,8 @@ pub struct LegalMove {
 
 #[derive(Debug, Clone, Copy)]
 pub struct RatedMove {
-    chess_move: LegalMove,
-    rating: i32,
+    pub chess_move: LegalMove,
+    pub rating: i32,
 }
 
 pub const KNIGHT_MOVES: [Move; 8] = [
@@ -75,7 +77,7 @@ pub const fn concat_const_arrays(arr_1: [Move; 4], arr_2: [Move; 4]) -> [Move; 8
     moves
 }
 
-fn file_to_char(file: usize) -> char {
+pub fn file_to_char(file: usize) -> char {
     match file {
         0 => 'a',
         1 => 'b',
@@ -89,6 +91,13 @@ fn file_to_char(file: usize) -> char {
     }
 }
 
+/*
+ * Output a square in algebraic notation, e.g. 0 -> a1 and 63 -> h8.
+ */
+pub fn square_to_string(position: usize) -> String {
+    format!("{}{}", file_to_char(position % 8), (position / 8) + 1)
+}
+
 fn promotion_to_char(promotion: i8) -> char {
     match promotion {
         WQUEEN | BQUEEN => 'q',
@@ -102,15 +111,13 @@ fn promotion_to_char(promotion: i8) -> char {
 /*
  * Output a legal move in UCI-compatible LAN format (Long Algebraic Notation)
  */
-impl ToString for LegalMove {
-    fn to_string(&self) -> String {
-        let from_file = file_to_char(self.from % 8);
-        let from_rank = (self.from / 8) + 1;
-
-        let to_file = file_to_char(self.to % 8);
-        let to_rank = (self.to / 8) + 1;
-
-        let mut move_string = format!("{from_file}{from_rank}{to_file}{to_rank}").to_string();
+impl fmt::Display for LegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut move_string = format!(
+            "{}{}",
+            square_to_string(self.from),
+            square_to_string(self.to)
+        );
 
         if let MoveType::PawnMove {
             promotion_move: Some(promotion),
@@ -118,25 +125,116 @@ impl ToString for LegalMove {
         {
             move_string.push(promotion_to_char(promotion));
         }
-        move_string
+        write!(f, "{move_string}")
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct LegalMoveParseError;
+pub enum LegalMoveParseErrorKind {
+    Malformed,
+    Illegal,
+    Ambiguous,
+    MissingPromotion,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LegalMoveParseError {
+    pub kind: LegalMoveParseErrorKind,
+    pub given_string: String,
+}
+
+impl LegalMoveParseError {
+    pub fn new(kind: LegalMoveParseErrorKind, given_string: String) -> Self {
+        Self { kind, given_string }
+    }
+}
+
+impl fmt::Display for LegalMoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            LegalMoveParseErrorKind::Malformed => {
+                write!(f, "'{}' is not a valid move", self.given_string)
+            }
+            LegalMoveParseErrorKind::Illegal => {
+                write!(f, "'{}' is not legal in this position", self.given_string)
+            }
+            LegalMoveParseErrorKind::Ambiguous => {
+                write!(
+                    f,
+                    "'{}' matches more than one legal move",
+                    self.given_string
+                )
+            }
+            LegalMoveParseErrorKind::MissingPromotion => {
+                write!(
+                    f,
+                    "'{}' is a promotion and needs a piece (q, r, b or n)",
+                    self.given_string
+                )
+            }
+        }
+    }
+}
+
+pub fn char_to_promotion(char: char, is_white: bool) -> Option<i8> {
+    let promotion = match char {
+        'q' => WQUEEN,
+        'r' => WROOK,
+        'b' => WBISHOP,
+        'n' => WKNIGHT,
+        _ => return None,
+    };
+
+    Some(if is_white { promotion } else { -promotion })
+}
 
 /*
- * Parse a legal move from a UCI-compatible LAN string.
+ * Parse a legal move from a UCI-compatible LAN string, e.g. e2e4 or e7e8q.
+ * Without a position only the squares and the promotion piece are known, the promotion color is taken from the
+ * rank the pawn lands on. Use ChessBoard::parse_uci_move to get the correct move type and capture flag.
  */
 impl FromStr for LegalMove {
     type Err = LegalMoveParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed =
+            || LegalMoveParseError::new(LegalMoveParseErrorKind::Malformed, s.to_string());
+
+        let chars: Vec<char> = s.chars().collect();
+
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(malformed());
+        }
+
+        let from = parse_square(chars[0], chars[1]).ok_or_else(malformed)?;
+        let to = parse_square(chars[2], chars[3]).ok_or_else(malformed)?;
+
+        let move_type = match chars.get(4) {
+            None => MoveType::Normal,
+            Some(char) => {
+                let to_rank = to / 8;
+
+                if to_rank != 7 && to_rank != 0 {
+                    return Err(malformed());
+                }
+
+                MoveType::PawnMove {
+                    promotion_move: Some(
+                        char_to_promotion(*char, to_rank == 7).ok_or_else(malformed)?,
+                    ),
+                }
+            }
+        };
+
         Ok(Self {
-            from: 0,
-            to: 0,
-            move_type: MoveType::Normal,
+            from,
+            to,
+            move_type,
             is_capture: false,
         })
     }
 }
+
+pub fn parse_square(file: char, rank: char) -> Option<usize> {
+    Some(parse_rank(rank)? * 8 + parse_file(file)?)
+}
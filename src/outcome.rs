@@ -0,0 +1,311 @@
+use crate::bitboard::piece_colour;
+use crate::chessboard::{
+    BBISHOP, BKING, BKNIGHT, ChessBoard, EMPTY, Players, WBISHOP, WKING, WKNIGHT, WPAWN, WQUEEN,
+    should_be_light_square,
+};
+use crate::engine::ChessEngine;
+use crate::moves::{LegalMove, MoveType};
+use std::fmt;
+
+/* Half moves without a capture or pawn move before a draw can be claimed, or is forced. */
+pub const FIFTY_MOVE_RULE_HALF_MOVES: u32 = 100;
+pub const SEVENTY_FIVE_MOVE_RULE_HALF_MOVES: u32 = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameOutcome {
+    Checkmate { winner: Players },
+    Stalemate,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+    Timeout { winner: Players },
+    TimeoutVsInsufficientMaterial, // The side that ran out of time can not be checkmated by the other.
+    Resignation { winner: Players },
+    DrawAgreed,
+}
+
+impl GameOutcome {
+    pub fn winner(&self) -> Option<Players> {
+        match self {
+            GameOutcome::Checkmate { winner }
+            | GameOutcome::Timeout { winner }
+            | GameOutcome::Resignation { winner } => Some(*winner),
+            _ => None,
+        }
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.winner().is_none()
+    }
+
+    /* Why the game ended, without who won. */
+    pub fn reason(&self) -> &'static str {
+        match self {
+            GameOutcome::Checkmate { .. } => "Checkmate",
+            GameOutcome::Stalemate => "Stalemate",
+            GameOutcome::FiftyMoveRule => "Fifty move rule",
+            GameOutcome::SeventyFiveMoveRule => "Seventy-five move rule",
+            GameOutcome::ThreefoldRepetition => "Threefold repetition",
+            GameOutcome::FivefoldRepetition => "Fivefold repetition",
+            GameOutcome::InsufficientMaterial => "Insufficient material",
+            GameOutcome::Timeout { .. } => "Timeout",
+            GameOutcome::TimeoutVsInsufficientMaterial => "Timeout vs insufficient material",
+            GameOutcome::Resignation { .. } => "Resignation",
+            GameOutcome::DrawAgreed => "Agreed draw",
+        }
+    }
+
+    /*
+     * The fifty move rule and threefold repetition only end the game when a player claims the draw,
+     * every other outcome ends the game immediately.
+     */
+    pub fn is_claimable(&self) -> bool {
+        matches!(
+            self,
+            GameOutcome::FiftyMoveRule | GameOutcome::ThreefoldRepetition
+        )
+    }
+
+    /*
+     * Whether the position on the board ended the game. Taking a move back undoes such an outcome, but not a
+     * resignation, an agreed draw or a timeout.
+     */
+    pub fn is_decided_by_position(&self) -> bool {
+        !matches!(
+            self,
+            GameOutcome::Timeout { .. }
+                | GameOutcome::TimeoutVsInsufficientMaterial
+                | GameOutcome::Resignation { .. }
+                | GameOutcome::DrawAgreed
+        )
+    }
+}
+
+impl fmt::Display for GameOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameOutcome::Checkmate {
+                winner: Players::White,
+            } => write!(f, "White wins by checkmate"),
+            GameOutcome::Checkmate {
+                winner: Players::Black,
+            } => write!(f, "Black wins by checkmate"),
+            GameOutcome::Stalemate => write!(f, "Draw by stalemate"),
+            GameOutcome::FiftyMoveRule => write!(f, "Draw by the fifty move rule"),
+            GameOutcome::SeventyFiveMoveRule => write!(f, "Draw by the seventy-five move rule"),
+            GameOutcome::ThreefoldRepetition => write!(f, "Draw by threefold repetition"),
+            GameOutcome::FivefoldRepetition => write!(f, "Draw by fivefold repetition"),
+            GameOutcome::InsufficientMaterial => write!(f, "Draw by insufficient material"),
+            GameOutcome::Timeout {
+                winner: Players::White,
+            } => write!(f, "White wins on time"),
+            GameOutcome::Timeout {
+                winner: Players::Black,
+            } => write!(f, "Black wins on time"),
+            GameOutcome::TimeoutVsInsufficientMaterial => {
+                write!(f, "Draw by timeout vs insufficient material")
+            }
+            GameOutcome::Resignation {
+                winner: Players::White,
+            } => write!(f, "White wins by resignation"),
+            GameOutcome::Resignation {
+                winner: Players::Black,
+            } => write!(f, "Black wins by resignation"),
+            GameOutcome::DrawAgreed => write!(f, "Draw by agreement"),
+        }
+    }
+}
+
+/*
+ * Decide the outcome of a position from its legal moves, whether the side to move is in check and the
+ * positions that were played before it (oldest first). Returns None if the game is still going.
+ */
+pub fn get_outcome(
+    chessboard: &ChessBoard,
+    legal_moves: &[LegalMove],
+    is_checked: bool,
+    previous_positions: &[ChessBoard],
+) -> Option<GameOutcome> {
+    if legal_moves.is_empty() {
+        if is_checked {
+            let winner = match chessboard.side_to_move {
+                Players::White => Players::Black,
+                Players::Black => Players::White,
+            };
+            return Some(GameOutcome::Checkmate { winner });
+        }
+        return Some(GameOutcome::Stalemate);
+    }
+
+    let repetitions = count_repetitions(chessboard, legal_moves, previous_positions);
+
+    if chessboard.half_move_clock >= SEVENTY_FIVE_MOVE_RULE_HALF_MOVES {
+        return Some(GameOutcome::SeventyFiveMoveRule);
+    }
+
+    if repetitions >= 5 {
+        return Some(GameOutcome::FivefoldRepetition);
+    }
+
+    if has_insufficient_material(&chessboard.board) {
+        return Some(GameOutcome::InsufficientMaterial);
+    }
+
+    if chessboard.half_move_clock >= FIFTY_MOVE_RULE_HALF_MOVES {
+        return Some(GameOutcome::FiftyMoveRule);
+    }
+
+    if repetitions >= 3 {
+        return Some(GameOutcome::ThreefoldRepetition);
+    }
+
+    None
+}
+
+/*
+ * Count how many times the current position has occurred, including the current occurrence.
+ * Only positions since the last capture or pawn move can be repetitions, as those moves cannot be undone.
+ */
+fn count_repetitions(
+    chessboard: &ChessBoard,
+    legal_moves: &[LegalMove],
+    previous_positions: &[ChessBoard],
+) -> usize {
+    let en_passant = effective_en_passant(chessboard, legal_moves);
+
+    let matching = previous_positions
+        .iter()
+        .rev()
+        .take(chessboard.half_move_clock as usize)
+        .filter(|previous| {
+            previous.board == chessboard.board
+                && previous.side_to_move == chessboard.side_to_move
+                && previous.castling_ability == chessboard.castling_ability
+                && previous_en_passant(previous) == en_passant
+        })
+        .count();
+
+    matching + 1
+}
+
+/*
+ * The en passant square only makes a position different if an en passant capture is actually possible.
+ */
+fn effective_en_passant(chessboard: &ChessBoard, legal_moves: &[LegalMove]) -> Option<usize> {
+    chessboard.en_passant_target_square.filter(|_| {
+        legal_moves
+            .iter()
+            .any(|legal_move| matches!(legal_move.move_type, MoveType::Enpassant { .. }))
+    })
+}
+
+fn previous_en_passant(previous: &ChessBoard) -> Option<usize> {
+    previous.en_passant_target_square?;
+
+    effective_en_passant(previous, &previous.legal_moves())
+}
+
+/*
+ * Positions where no sequence of legal moves can lead to a checkmate:
+ * K vs K, K and a minor piece vs K, and kings with any number of bishops all on the same square color.
+ */
+pub fn has_insufficient_material(board: &[i8; 64]) -> bool {
+    let mut knights = 0;
+    let mut bishops_on_light = 0;
+    let mut bishops_on_dark = 0;
+
+    for (position, square) in board.iter().enumerate() {
+        match *square {
+            EMPTY | WKING | BKING => {}
+            WKNIGHT | BKNIGHT => knights += 1,
+            WBISHOP | BBISHOP => {
+                if should_be_light_square(position) {
+                    bishops_on_light += 1;
+                } else {
+                    bishops_on_dark += 1;
+                }
+            }
+            _ => return false,
+        }
+    }
+
+    let bishops = bishops_on_light + bishops_on_dark;
+
+    match (knights, bishops) {
+        (0, 0) | (1, 0) | (0, 1) => true,
+        (0, _) => bishops_on_light == 0 || bishops_on_dark == 0,
+        _ => false,
+    }
+}
+
+/*
+ * The outcome when a side runs out of time: the other side wins, unless no series of legal moves lets it
+ * checkmate. The pieces of the flagged side count too, they can block in their own king.
+ */
+pub fn timeout_outcome(board: &[i8; 64], flagged: Players) -> GameOutcome {
+    let winner = match flagged {
+        Players::White => Players::Black,
+        Players::Black => Players::White,
+    };
+
+    if has_mating_material(board, winner) {
+        GameOutcome::Timeout { winner }
+    } else {
+        GameOutcome::TimeoutVsInsufficientMaterial
+    }
+}
+
+/*
+ * Whether one side could checkmate by some series of legal moves. Any pawn, rook or queen, or two minor pieces
+ * that are not all bishops on the same square color, are enough. A lone knight needs an enemy piece other than a
+ * queen to block in the king, bishops on one square color need an enemy pawn, knight or bishop on the other color.
+ */
+pub fn has_mating_material(board: &[i8; 64], side: Players) -> bool {
+    let mut knights = 0;
+    let mut bishops_on_light = 0;
+    let mut bishops_on_dark = 0;
+
+    for (position, square) in board.iter().enumerate() {
+        if *square == EMPTY || piece_colour(*square) != side {
+            continue;
+        }
+
+        match square.abs() {
+            WKING => {}
+            WKNIGHT => knights += 1,
+            WBISHOP => {
+                if should_be_light_square(position) {
+                    bishops_on_light += 1;
+                } else {
+                    bishops_on_dark += 1;
+                }
+            }
+            _ => return true,
+        }
+    }
+
+    match (knights, bishops_on_light + bishops_on_dark) {
+        (0, 0) => false,
+        (1, 0) => opponent_has(board, side, |_, piece| piece != WQUEEN),
+        (0, _) if bishops_on_light == 0 || bishops_on_dark == 0 => {
+            opponent_has(board, side, |position, piece| match piece {
+                WPAWN | WKNIGHT => true,
+                WBISHOP => should_be_light_square(position) != (bishops_on_light > 0),
+                _ => false,
+            })
+        }
+        _ => true,
+    }
+}
+
+/* Whether the opponent of a side has a piece besides its king that matches, given its square and white piece. */
+fn opponent_has(board: &[i8; 64], side: Players, matches: impl Fn(usize, i8) -> bool) -> bool {
+    board.iter().enumerate().any(|(position, square)| {
+        *square != EMPTY
+            && piece_colour(*square) != side
+            && square.abs() != WKING
+            && matches(position, square.abs())
+    })
+}
@@ -0,0 +1,433 @@
+use crate::chessboard::ChessBoard;
+use crate::engine::ChessEngine;
+use crate::fen::{FEN_STARTING_POSITION, Fen, FenErrorKind};
+use crate::transposition::{MAX_HASH_MB, PerftTable};
+use crate::uci::nodes_per_second;
+use std::fmt;
+use std::time::Instant;
+
+/*
+ * The arguments of the perft subcommand:
+ * perft [--fen <fen>] --depth <n> [--divide] [--threads <n>] [--hash <mb>]
+ * Without a FEN the start position is used, without --hash no table is used.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerftCommand {
+    pub fen: String,
+    pub depth: u64,
+    pub divide: bool,
+    pub threads: usize,
+    pub hash_mb: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PerftCommandErrorKind {
+    UnknownArgument,
+    MissingValue,
+    InvalidValue,
+    MissingDepth,
+    InvalidFen(FenErrorKind),
+    InvalidEpd,
+    Io(std::io::ErrorKind),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PerftCommandError {
+    pub kind: PerftCommandErrorKind,
+    pub given_string: String,
+}
+
+impl PerftCommandError {
+    pub fn new(kind: PerftCommandErrorKind, given_string: String) -> Self {
+        Self { kind, given_string }
+    }
+}
+
+impl fmt::Display for PerftCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PerftCommandErrorKind::UnknownArgument => {
+                write!(f, "unknown argument: {}", self.given_string)
+            }
+            PerftCommandErrorKind::MissingValue => {
+                write!(f, "{} needs a value", self.given_string)
+            }
+            PerftCommandErrorKind::InvalidValue => {
+                write!(f, "invalid value: {}", self.given_string)
+            }
+            PerftCommandErrorKind::MissingDepth => write!(f, "--depth is required"),
+            PerftCommandErrorKind::InvalidFen(kind) => {
+                write!(f, "invalid FEN ({kind:?}): {}", self.given_string)
+            }
+            PerftCommandErrorKind::InvalidEpd => {
+                write!(f, "invalid perft EPD line: {}", self.given_string)
+            }
+            PerftCommandErrorKind::Io(kind) => {
+                write!(f, "could not read {} ({kind:?})", self.given_string)
+            }
+        }
+    }
+}
+
+impl PerftCommand {
+    pub fn parse<S: AsRef<str>>(arguments: &[S]) -> Result<Self, PerftCommandError> {
+        let mut fen = String::from(FEN_STARTING_POSITION);
+        let mut depth = None;
+        let mut divide = false;
+        let mut threads = 1;
+        let mut hash_mb = None;
+
+        let mut arguments = arguments.iter().map(AsRef::as_ref);
+
+        while let Some(argument) = arguments.next() {
+            let mut value = || {
+                arguments.next().ok_or_else(|| {
+                    PerftCommandError::new(
+                        PerftCommandErrorKind::MissingValue,
+                        argument.to_string(),
+                    )
+                })
+            };
+
+            match argument {
+                "--fen" => fen = value()?.to_string(),
+                "--depth" => depth = Some(parse_number(argument, value()?, 1)?),
+                "--threads" => threads = parse_number(argument, value()?, 1)?,
+                "--hash" => {
+                    hash_mb = Some(parse_number(argument, value()?, 1)?.min(MAX_HASH_MB));
+                }
+                "--divide" => divide = true,
+                _ => {
+                    return Err(PerftCommandError::new(
+                        PerftCommandErrorKind::UnknownArgument,
+                        argument.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let Some(depth) = depth else {
+            return Err(PerftCommandError::new(
+                PerftCommandErrorKind::MissingDepth,
+                String::new(),
+            ));
+        };
+
+        Ok(Self {
+            fen,
+            depth,
+            divide,
+            threads,
+            hash_mb,
+        })
+    }
+
+    /*
+     * Count the nodes and return the lines to print: the divide counts in the "move: count" format Stockfish
+     * uses if asked for, then the total and the speed.
+     */
+    pub fn run(&self) -> Result<Vec<String>, PerftCommandError> {
+        let board = board_from_fen(&self.fen)?;
+
+        let table = self.hash_mb.map(PerftTable::new);
+
+        let start = Instant::now();
+        let (divide, nodes) = board.parallel_perft(self.depth, self.threads, table.as_ref());
+        let elapsed = start.elapsed();
+
+        let mut lines = Vec::new();
+
+        if self.divide {
+            lines.extend(
+                divide
+                    .into_iter()
+                    .map(|(uci_move, count)| format!("{uci_move}: {count}")),
+            );
+            lines.push(String::new());
+        }
+
+        lines.push(format!("Nodes searched: {nodes}"));
+        lines.push(format!("Time: {} ms", elapsed.as_millis()));
+        lines.push(format!(
+            "Nodes per second: {}",
+            nodes_per_second(nodes, elapsed)
+        ));
+
+        Ok(lines)
+    }
+}
+
+fn board_from_fen(fen: &str) -> Result<ChessBoard, PerftCommandError> {
+    ChessBoard::set_fen_position(fen).map_err(|e| {
+        PerftCommandError::new(PerftCommandErrorKind::InvalidFen(e.kind), e.given_string)
+    })
+}
+
+fn parse_number<T: std::str::FromStr + PartialOrd>(
+    argument: &str,
+    value: &str,
+    minimum: T,
+) -> Result<T, PerftCommandError> {
+    value
+        .parse()
+        .ok()
+        .filter(|number| *number >= minimum)
+        .ok_or_else(|| {
+            PerftCommandError::new(
+                PerftCommandErrorKind::InvalidValue,
+                format!("{argument} {value}"),
+            )
+        })
+}
+
+/*
+ * Run the perft subcommand with the arguments that follow "perft". Returns false if they were invalid.
+ */
+pub fn run_perft<S: AsRef<str>>(arguments: &[S]) -> bool {
+    match PerftCommand::parse(arguments).and_then(|command| command.run()) {
+        Ok(lines) => {
+            lines.iter().for_each(|line| println!("{line}"));
+            true
+        }
+        Err(e) => {
+            eprintln!("perft: {e}");
+            eprintln!(
+                "usage: chess perft [--fen <fen>] --depth <n> [--divide] [--threads <n>] [--hash <mb>]"
+            );
+            false
+        }
+    }
+}
+
+/*
+ * One line of a perft suite in EPD form, e.g. "<fen> ;D1 20 ;D2 400 ;D3 8902".
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerftSuiteEntry {
+    pub fen: String,
+    pub expected: Vec<(u64, u64)>, /* (depth, nodes) in the order of the line */
+}
+
+impl PerftSuiteEntry {
+    /*
+     * Returns None for empty lines and lines starting with #. A position that is no valid FEN is an InvalidFen
+     * error holding the line.
+     */
+    pub fn parse(line: &str) -> Result<Option<Self>, PerftCommandError> {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let invalid =
+            || PerftCommandError::new(PerftCommandErrorKind::InvalidEpd, line.to_string());
+
+        let mut fields = line.split(';');
+        let fen = fields.next().map(str::trim).unwrap_or_default();
+
+        let expected = fields
+            .map(|field| {
+                let (depth, nodes) = field.trim().split_once(' ').ok_or_else(invalid)?;
+                let depth = depth.strip_prefix('D').ok_or_else(invalid)?;
+
+                match (depth.parse(), nodes.trim().parse()) {
+                    (Ok(depth), Ok(nodes)) => Ok((depth, nodes)),
+                    _ => Err(invalid()),
+                }
+            })
+            .collect::<Result<Vec<(u64, u64)>, _>>()?;
+
+        if fen.is_empty() || expected.is_empty() {
+            return Err(invalid());
+        }
+
+        ChessBoard::set_fen_position(fen).map_err(|e| {
+            PerftCommandError::new(PerftCommandErrorKind::InvalidFen(e.kind), line.to_string())
+        })?;
+
+        Ok(Some(Self {
+            fen: fen.to_string(),
+            expected,
+        }))
+    }
+}
+
+pub fn parse_perft_suite(contents: &str) -> Result<Vec<PerftSuiteEntry>, PerftCommandError> {
+    contents
+        .lines()
+        .filter_map(|line| PerftSuiteEntry::parse(line).transpose())
+        .collect()
+}
+
+/*
+ * A depth of a suite position where the node count differs, with the divide at that depth to narrow it down.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerftMismatch {
+    pub fen: String,
+    pub depth: u64,
+    pub expected: u64,
+    pub found: u64,
+    pub divide: Vec<(String, u64)>,
+}
+
+impl fmt::Display for PerftMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} ;D{} expected {} but found {}",
+            self.fen, self.depth, self.expected, self.found
+        )?;
+
+        for (uci_move, count) in &self.divide {
+            writeln!(f, "{uci_move}: {count}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/*
+ * Check every depth of every entry up to max_depth, report is called with each entry and the mismatch if it has
+ * one. An entry stops at its first wrong depth, since every deeper count is wrong too.
+ */
+pub fn run_perft_suite<F>(
+    entries: &[PerftSuiteEntry],
+    max_depth: Option<u64>,
+    threads: usize,
+    mut report: F,
+) -> Result<Vec<PerftMismatch>, PerftCommandError>
+where
+    F: FnMut(&PerftSuiteEntry, Option<&PerftMismatch>),
+{
+    let mut mismatches = Vec::new();
+
+    for entry in entries {
+        let board = ChessBoard::set_fen_position(&entry.fen).map_err(|e| {
+            PerftCommandError::new(PerftCommandErrorKind::InvalidFen(e.kind), entry.fen.clone())
+        })?;
+
+        let mismatch = entry
+            .expected
+            .iter()
+            .filter(|(depth, _)| max_depth.is_none_or(|max_depth| *depth <= max_depth))
+            .find_map(|&(depth, expected)| {
+                let (divide, found) = board.parallel_perft(depth, threads, None);
+
+                (found != expected).then(|| PerftMismatch {
+                    fen: entry.fen.clone(),
+                    depth,
+                    expected,
+                    found,
+                    divide,
+                })
+            });
+
+        report(entry, mismatch.as_ref());
+        mismatches.extend(mismatch);
+    }
+
+    Ok(mismatches)
+}
+
+/*
+ * The arguments of the perft-suite subcommand: perft-suite <file> [--depth <max depth>] [--threads <n>]
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerftSuiteCommand {
+    pub path: String,
+    pub max_depth: Option<u64>,
+    pub threads: usize,
+}
+
+impl PerftSuiteCommand {
+    pub fn parse<S: AsRef<str>>(arguments: &[S]) -> Result<Self, PerftCommandError> {
+        let mut path = None;
+        let mut max_depth = None;
+        let mut threads = 1;
+
+        let mut arguments = arguments.iter().map(AsRef::as_ref);
+
+        while let Some(argument) = arguments.next() {
+            let mut value = || {
+                arguments.next().ok_or_else(|| {
+                    PerftCommandError::new(
+                        PerftCommandErrorKind::MissingValue,
+                        argument.to_string(),
+                    )
+                })
+            };
+
+            match argument {
+                "--depth" => max_depth = Some(parse_number(argument, value()?, 1)?),
+                "--threads" => threads = parse_number(argument, value()?, 1)?,
+                _ if path.is_none() && !argument.starts_with("--") => {
+                    path = Some(argument.to_string());
+                }
+                _ => {
+                    return Err(PerftCommandError::new(
+                        PerftCommandErrorKind::UnknownArgument,
+                        argument.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let Some(path) = path else {
+            return Err(PerftCommandError::new(
+                PerftCommandErrorKind::MissingValue,
+                String::from("perft-suite"),
+            ));
+        };
+
+        Ok(Self {
+            path,
+            max_depth,
+            threads,
+        })
+    }
+}
+
+/*
+ * Run the perft-suite subcommand with the arguments that follow "perft-suite".
+ * Returns false if the arguments or the file were invalid or any count was wrong.
+ */
+pub fn run_perft_suite_command<S: AsRef<str>>(arguments: &[S]) -> bool {
+    let start = Instant::now();
+
+    let result = PerftSuiteCommand::parse(arguments).and_then(|command| {
+        let contents = std::fs::read_to_string(&command.path).map_err(|e| {
+            PerftCommandError::new(PerftCommandErrorKind::Io(e.kind()), command.path.clone())
+        })?;
+
+        let entries = parse_perft_suite(&contents)?;
+
+        let mismatches = run_perft_suite(
+            &entries,
+            command.max_depth,
+            command.threads,
+            |entry, mismatch| match mismatch {
+                Some(mismatch) => println!("FAIL {mismatch}"),
+                None => println!("ok   {}", entry.fen),
+            },
+        )?;
+
+        Ok((entries.len(), mismatches.len()))
+    });
+
+    match result {
+        Ok((positions, mismatches)) => {
+            println!(
+                "{positions} positions, {mismatches} failed, {} ms",
+                start.elapsed().as_millis()
+            );
+            mismatches == 0
+        }
+        Err(e) => {
+            eprintln!("perft-suite: {e}");
+            eprintln!("usage: chess perft-suite <file> [--depth <max depth>] [--threads <n>]");
+            false
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::chessboard::{ChessBoard, Players};
+use crate::fen::{Fen, FenErrorKind};
+use crate::moves::{LegalMove, LegalMoveParseErrorKind};
+use crate::outcome::GameOutcome;
+use std::fmt;
+
+pub mod reading;
+pub mod writing;
+
+pub use reading::PgnReader;
+
+/* The tags every PGN game has to contain, in the order they have to be exported. */
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PgnResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Unknown,
+}
+
+impl PgnResult {
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(PgnResult::WhiteWins),
+            "0-1" => Some(PgnResult::BlackWins),
+            "1/2-1/2" => Some(PgnResult::Draw),
+            "*" => Some(PgnResult::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl From<GameOutcome> for PgnResult {
+    fn from(outcome: GameOutcome) -> Self {
+        match outcome.winner() {
+            Some(Players::White) => PgnResult::WhiteWins,
+            Some(Players::Black) => PgnResult::BlackWins,
+            None => PgnResult::Draw,
+        }
+    }
+}
+
+impl fmt::Display for PgnResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnResult::WhiteWins => write!(f, "1-0"),
+            PgnResult::BlackWins => write!(f, "0-1"),
+            PgnResult::Draw => write!(f, "1/2-1/2"),
+            PgnResult::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub san: String,
+    pub legal_move: LegalMove,
+    pub nags: Vec<u8>,
+    pub comments: Vec<String>,         /* Comments following the move */
+    pub variations: Vec<PgnVariation>, /* Alternatives to this move, played from the position before it */
+}
+
+impl PgnMove {
+    pub fn new(san: String, legal_move: LegalMove) -> Self {
+        Self {
+            san,
+            legal_move,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PgnVariation {
+    pub initial_comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub starting_position: ChessBoard,
+    pub initial_comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+    pub result: PgnResult,
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new(ChessBoard::default())
+    }
+}
+
+impl PgnGame {
+    /*
+     * Create an empty game. A starting position other than the standard one is recorded with the SetUp and FEN tags.
+     */
+    pub fn new(starting_position: ChessBoard) -> Self {
+        let mut game = Self {
+            tags: Vec::new(),
+            starting_position,
+            initial_comments: Vec::new(),
+            moves: Vec::new(),
+            result: PgnResult::Unknown,
+        };
+
+        if game.starting_position != ChessBoard::default() {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &game.starting_position.to_fen());
+        }
+
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some((_, tag_value)) => *tag_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn set_result(&mut self, result: PgnResult) {
+        self.result = result;
+        self.set_tag("Result", &result.to_string());
+    }
+
+    /*
+     * Append a legal move of the current final position to the main line.
+     */
+    pub fn push_move(&mut self, legal_move: LegalMove) {
+        let board = self.final_position();
+        self.moves
+            .push(PgnMove::new(board.move_to_san(legal_move), legal_move));
+    }
+
+    /*
+     * The position after replaying every move of the main line.
+     */
+    pub fn final_position(&self) -> ChessBoard {
+        self.positions()
+            .pop()
+            .expect("There is always a starting position")
+    }
+
+    /*
+     * Every position of the main line, starting with the starting position.
+     */
+    pub fn positions(&self) -> Vec<ChessBoard> {
+        let mut board = self.starting_position.clone();
+        let mut positions = Vec::with_capacity(self.moves.len() + 1);
+
+        for pgn_move in &self.moves {
+            positions.push(board.clone());
+            board.make_move(pgn_move.legal_move);
+        }
+        positions.push(board);
+
+        positions
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PgnErrorKind {
+    InvalidTag,
+    InvalidFen(FenErrorKind),
+    InvalidMove(LegalMoveParseErrorKind),
+    UnexpectedToken,
+    UnterminatedComment,
+    UnbalancedVariation,
+    Io(std::io::ErrorKind),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PgnError {
+    pub kind: PgnErrorKind,
+    pub given_string: String,
+}
+
+impl PgnError {
+    pub fn new(kind: PgnErrorKind, given_string: String) -> Self {
+        Self { kind, given_string }
+    }
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PgnErrorKind::InvalidTag => write!(f, "invalid tag pair: {}", self.given_string),
+            PgnErrorKind::InvalidFen(kind) => {
+                write!(f, "invalid FEN tag ({kind:?}): {}", self.given_string)
+            }
+            PgnErrorKind::InvalidMove(kind) => {
+                write!(f, "invalid move ({kind:?}): {}", self.given_string)
+            }
+            PgnErrorKind::UnexpectedToken => {
+                write!(f, "unexpected token: {}", self.given_string)
+            }
+            PgnErrorKind::UnterminatedComment => {
+                write!(f, "unterminated comment: {}", self.given_string)
+            }
+            PgnErrorKind::UnbalancedVariation => {
+                write!(f, "unbalanced variation: {}", self.given_string)
+            }
+            PgnErrorKind::Io(kind) => write!(f, "could not read PGN: {kind}"),
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use super::{PgnError, PgnErrorKind, PgnGame, PgnMove, PgnResult, PgnVariation};
+use crate::chessboard::ChessBoard;
+use crate::fen::Fen;
+use std::io::{BufRead, Lines};
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PgnToken {
+    Move(String),
+    Nag(u8),
+    Comment(String),
+    VariationStart,
+    VariationEnd,
+    Result(PgnResult),
+}
+
+/*
+ * What the movetext read so far ends in, kept up to date line by line so a long game is not scanned again for
+ * every new line.
+ */
+#[derive(Debug, Default)]
+struct MovetextState {
+    in_comment: bool, // Inside a {} comment that continues on the next line.
+    variation_depth: usize,
+    ends_with_result: bool,
+}
+
+impl MovetextState {
+    fn scan_line(&mut self, line: &str) {
+        let mut chars = line.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            if self.in_comment {
+                self.in_comment = char != '}';
+                continue;
+            }
+
+            if char.is_whitespace() {
+                continue;
+            }
+
+            self.ends_with_result = false;
+
+            match char {
+                '{' => self.in_comment = true,
+                ';' => break, // The comment runs to the end of the line.
+                '(' => self.variation_depth += 1,
+                ')' => self.variation_depth = self.variation_depth.saturating_sub(1),
+                '$' => while chars.next_if(|char| char.is_ascii_digit()).is_some() {},
+                _ => {
+                    let mut symbol = String::from(char);
+
+                    while let Some(char) = chars.next_if(|char| {
+                        !char.is_whitespace() && !matches!(char, '{' | '}' | '(' | ')' | ';' | '$')
+                    }) {
+                        symbol.push(char);
+                    }
+
+                    self.ends_with_result = PgnResult::from_token(&symbol).is_some();
+                }
+            }
+        }
+    }
+
+    /* The game ends with a result token outside of any comment or variation. */
+    fn is_complete(&self) -> bool {
+        self.ends_with_result && !self.in_comment && self.variation_depth == 0
+    }
+}
+
+/*
+ * Streams the games of a (multi-game) PGN file one at a time, so only the game being parsed is kept in memory.
+ */
+pub struct PgnReader<R: BufRead> {
+    lines: Lines<R>,
+    pending_line: Option<String>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            pending_line: None,
+        }
+    }
+
+    /*
+     * Collect the tag lines and the movetext of the next game. The movetext ends with a result token outside of
+     * comments and variations, or when the tags of the next game start.
+     */
+    fn read_game_text(&mut self) -> Option<Result<(Vec<String>, String), PgnError>> {
+        let mut tag_lines = Vec::new();
+        let mut movetext = String::new();
+        let mut state = MovetextState::default();
+
+        loop {
+            let line = match self.pending_line.take() {
+                Some(line) => line,
+                None => match self.lines.next() {
+                    None => break,
+                    Some(Err(e)) => {
+                        return Some(Err(PgnError::new(
+                            PgnErrorKind::Io(e.kind()),
+                            e.to_string(),
+                        )));
+                    }
+                    Some(Ok(line)) => line,
+                },
+            };
+
+            let trimmed = line.trim();
+
+            // Lines starting with % are escaped and should be ignored.
+            if line.starts_with('%') {
+                continue;
+            }
+
+            if movetext.trim().is_empty() {
+                if trimmed.is_empty() {
+                    continue;
+                }
+
+                if trimmed.starts_with('[') {
+                    tag_lines.push(trimmed.to_string());
+                    continue;
+                }
+            } else if trimmed.starts_with('[') && !state.in_comment {
+                self.pending_line = Some(line);
+                break;
+            }
+
+            movetext.push_str(&line);
+            movetext.push('\n');
+            state.scan_line(&line);
+
+            if state.is_complete() {
+                break;
+            }
+        }
+
+        if tag_lines.is_empty() && movetext.trim().is_empty() {
+            return None;
+        }
+
+        Some(Ok((tag_lines, movetext)))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.read_game_text()? {
+            Ok((tag_lines, movetext)) => parse_game(&tag_lines, &movetext),
+            Err(e) => Err(e),
+        })
+    }
+}
+
+/*
+ * Parse a single game from a string.
+ */
+pub fn parse_pgn(pgn: &str) -> Result<PgnGame, PgnError> {
+    PgnReader::new(pgn.as_bytes())
+        .next()
+        .unwrap_or_else(|| Err(PgnError::new(PgnErrorKind::UnexpectedToken, String::new())))
+}
+
+fn parse_game(tag_lines: &[String], movetext: &str) -> Result<PgnGame, PgnError> {
+    let mut tags = Vec::new();
+
+    for line in tag_lines {
+        tags.append(&mut parse_tag_line(line)?);
+    }
+
+    let fen_tag = tags.iter().find(|(name, _)| name == "FEN");
+
+    let starting_position = match fen_tag {
+        Some((_, fen)) => ChessBoard::set_fen_position(fen)
+            .map_err(|e| PgnError::new(PgnErrorKind::InvalidFen(e.kind), fen.clone()))?,
+        None => ChessBoard::default(),
+    };
+
+    let mut tokens = tokenize(movetext)?.into_iter().peekable();
+
+    let (main_line, result) = parse_variation(&mut tokens, &starting_position, 0)?;
+
+    if let Some(token) = tokens.next() {
+        return Err(PgnError::new(
+            PgnErrorKind::UnexpectedToken,
+            format!("{token:?}"),
+        ));
+    }
+
+    let result = result
+        .or_else(|| {
+            tags.iter()
+                .find(|(name, _)| name == "Result")
+                .and_then(|(_, value)| PgnResult::from_token(value))
+        })
+        .unwrap_or(PgnResult::Unknown);
+
+    Ok(PgnGame {
+        tags,
+        starting_position,
+        initial_comments: main_line.initial_comments,
+        moves: main_line.moves,
+        result,
+    })
+}
+
+/*
+ * Parse one or more tag pairs of the form [Name "value"], where the value may escape " and \ with a backslash.
+ */
+fn parse_tag_line(line: &str) -> Result<Vec<(String, String)>, PgnError> {
+    let invalid = || PgnError::new(PgnErrorKind::InvalidTag, line.to_string());
+
+    let mut tags = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if char.is_whitespace() {
+            continue;
+        }
+
+        if char != '[' {
+            return Err(invalid());
+        }
+
+        let mut name = String::new();
+
+        while let Some(char) = chars.next_if(|char| char.is_alphanumeric() || *char == '_') {
+            name.push(char);
+        }
+
+        while chars.next_if(|char| char.is_whitespace()).is_some() {}
+
+        if name.is_empty() || chars.next() != Some('"') {
+            return Err(invalid());
+        }
+
+        let mut value = String::new();
+
+        loop {
+            match chars.next().ok_or_else(invalid)? {
+                '\\' => value.push(chars.next().ok_or_else(invalid)?),
+                '"' => break,
+                char => value.push(char),
+            }
+        }
+
+        while chars.next_if(|char| char.is_whitespace()).is_some() {}
+
+        if chars.next() != Some(']') {
+            return Err(invalid());
+        }
+
+        tags.push((name, value));
+    }
+
+    Ok(tags)
+}
+
+pub fn tokenize(movetext: &str) -> Result<Vec<PgnToken>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = movetext.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            _ if char.is_whitespace() => {}
+
+            '{' => {
+                let mut comment = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(char) => comment.push(char),
+                        None => {
+                            return Err(PgnError::new(PgnErrorKind::UnterminatedComment, comment));
+                        }
+                    }
+                }
+                tokens.push(PgnToken::Comment(comment.trim().to_string()));
+            }
+
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|char| *char != '\n').collect();
+                tokens.push(PgnToken::Comment(comment.trim().to_string()));
+            }
+
+            '(' => tokens.push(PgnToken::VariationStart),
+            ')' => tokens.push(PgnToken::VariationEnd),
+
+            '$' => {
+                let mut nag = String::new();
+
+                while let Some(digit) = chars.next_if(|char| char.is_ascii_digit()) {
+                    nag.push(digit);
+                }
+
+                let nag = nag
+                    .parse()
+                    .map_err(|_| PgnError::new(PgnErrorKind::UnexpectedToken, format!("${nag}")))?;
+
+                tokens.push(PgnToken::Nag(nag));
+            }
+
+            _ => {
+                let mut symbol = String::from(char);
+
+                while let Some(char) = chars.next_if(|char| {
+                    !char.is_whitespace() && !matches!(char, '{' | '}' | '(' | ')' | ';' | '$')
+                }) {
+                    symbol.push(char);
+                }
+
+                tokenize_symbol(&symbol, &mut tokens)?;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/*
+ * A symbol is a result, a move number, a move or a move number directly followed by a move, e.g. 1.e4.
+ * Suffix annotations like ! and ?! are turned into their NAG.
+ */
+fn tokenize_symbol(symbol: &str, tokens: &mut Vec<PgnToken>) -> Result<(), PgnError> {
+    if let Some(result) = PgnResult::from_token(symbol) {
+        tokens.push(PgnToken::Result(result));
+        return Ok(());
+    }
+
+    let without_number = symbol.trim_start_matches(|char: char| char.is_ascii_digit());
+
+    let san = if without_number.len() < symbol.len() && without_number.starts_with('.') {
+        without_number.trim_start_matches('.')
+    } else {
+        symbol
+    };
+
+    if san.is_empty() {
+        return Ok(());
+    }
+
+    let annotation_start = san.find(['!', '?']).unwrap_or(san.len());
+
+    let (san, annotation) = san.split_at(annotation_start);
+
+    if san.is_empty() {
+        return Err(PgnError::new(
+            PgnErrorKind::UnexpectedToken,
+            symbol.to_string(),
+        ));
+    }
+
+    tokens.push(PgnToken::Move(san.to_string()));
+
+    let nag = match annotation {
+        "" => return Ok(()),
+        "!" => 1,
+        "?" => 2,
+        "!!" => 3,
+        "??" => 4,
+        "!?" => 5,
+        "?!" => 6,
+        _ => {
+            return Err(PgnError::new(
+                PgnErrorKind::UnexpectedToken,
+                symbol.to_string(),
+            ));
+        }
+    };
+
+    tokens.push(PgnToken::Nag(nag));
+
+    Ok(())
+}
+
+/*
+ * Parse and replay a line of moves from the given position. Variations are parsed recursively from the position
+ * before the move they replace. The main line (depth 0) ends with an optional result, variations with a ')'.
+ */
+fn parse_variation(
+    tokens: &mut Peekable<IntoIter<PgnToken>>,
+    starting_position: &ChessBoard,
+    depth: usize,
+) -> Result<(PgnVariation, Option<PgnResult>), PgnError> {
+    let mut variation = PgnVariation::default();
+    let mut board = starting_position.clone();
+    let mut previous_board: Option<ChessBoard> = None;
+
+    while let Some(token) = tokens.next() {
+        match token {
+            PgnToken::Move(san) => {
+                let legal_move = board.parse_san_move(&san).map_err(|e| {
+                    PgnError::new(PgnErrorKind::InvalidMove(e.kind), e.given_string)
+                })?;
+
+                variation
+                    .moves
+                    .push(PgnMove::new(board.move_to_san(legal_move), legal_move));
+
+                previous_board = Some(board.clone());
+                board.make_move(legal_move);
+            }
+
+            PgnToken::Nag(nag) => match variation.moves.last_mut() {
+                Some(last_move) => last_move.nags.push(nag),
+                None => {
+                    return Err(PgnError::new(
+                        PgnErrorKind::UnexpectedToken,
+                        format!("${nag}"),
+                    ));
+                }
+            },
+
+            PgnToken::Comment(comment) => match variation.moves.last_mut() {
+                Some(last_move) => last_move.comments.push(comment),
+                None => variation.initial_comments.push(comment),
+            },
+
+            PgnToken::VariationStart => {
+                let variation_start = previous_board.as_ref().ok_or_else(|| {
+                    PgnError::new(PgnErrorKind::UnbalancedVariation, String::from("("))
+                })?;
+
+                let (alternative, _) = parse_variation(tokens, variation_start, depth + 1)?;
+
+                variation
+                    .moves
+                    .last_mut()
+                    .expect("A previous board means there is a move")
+                    .variations
+                    .push(alternative);
+            }
+
+            PgnToken::VariationEnd => {
+                if depth == 0 {
+                    return Err(PgnError::new(
+                        PgnErrorKind::UnbalancedVariation,
+                        String::from(")"),
+                    ));
+                }
+                return Ok((variation, None));
+            }
+
+            PgnToken::Result(result) => {
+                if depth > 0 {
+                    return Err(PgnError::new(
+                        PgnErrorKind::UnbalancedVariation,
+                        result.to_string(),
+                    ));
+                }
+                return Ok((variation, Some(result)));
+            }
+        }
+    }
+
+    if depth > 0 {
+        return Err(PgnError::new(
+            PgnErrorKind::UnbalancedVariation,
+            String::from("("),
+        ));
+    }
+
+    Ok((variation, None))
+}
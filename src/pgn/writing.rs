@@ -0,0 +1,191 @@
+use super::{PgnGame, PgnMove, PgnVariation, SEVEN_TAG_ROSTER};
+use crate::chessboard::Players;
+use std::fmt;
+
+/* Export format lines may not be longer than 80 characters. */
+pub const MAX_LINE_LENGTH: usize = 80;
+
+/*
+ * Write a game in PGN export format: the seven tag roster first, then any other tags,
+ * an empty line and the movetext wrapped at 80 columns, ending with the result.
+ */
+pub fn write_pgn(game: &PgnGame) -> String {
+    let mut pgn = String::new();
+
+    for name in SEVEN_TAG_ROSTER {
+        let value = match name {
+            "Result" => game.result.to_string(),
+            _ => game
+                .tag(name)
+                .map(str::to_string)
+                .unwrap_or_else(|| default_tag_value(name).to_string()),
+        };
+        pgn.push_str(&write_tag(name, &value));
+    }
+
+    for (name, value) in &game.tags {
+        if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+            pgn.push_str(&write_tag(name, value));
+        }
+    }
+
+    pgn.push('\n');
+
+    let mut words = Vec::new();
+
+    push_comments(&mut words, &game.initial_comments);
+    push_moves(
+        &mut words,
+        &game.moves,
+        game.starting_position.full_move_counter.max(1),
+        game.starting_position.side_to_move,
+    );
+    words.push(game.result.to_string());
+
+    pgn.push_str(&wrap_words(&words));
+    pgn.push('\n');
+
+    pgn
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", write_pgn(self))
+    }
+}
+
+fn default_tag_value(name: &str) -> &'static str {
+    match name {
+        "Date" => "????.??.??",
+        "Result" => "*",
+        _ => "?",
+    }
+}
+
+fn write_tag(name: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+
+    format!("[{name} \"{escaped}\"]\n")
+}
+
+/*
+ * Push the words of a line of moves. Black moves get a "N..." move number when they start the line
+ * or follow a comment or a variation.
+ */
+fn push_moves(
+    words: &mut Vec<String>,
+    moves: &[PgnMove],
+    mut move_number: u32,
+    mut side_to_move: Players,
+) {
+    let mut needs_number = true;
+
+    for pgn_move in moves {
+        match side_to_move {
+            Players::White => words.push(format!("{move_number}.")),
+            Players::Black if needs_number => words.push(format!("{move_number}...")),
+            Players::Black => {}
+        }
+
+        words.push(pgn_move.san.clone());
+        needs_number = false;
+
+        for nag in &pgn_move.nags {
+            words.push(format!("${nag}"));
+        }
+
+        if !pgn_move.comments.is_empty() {
+            push_comments(words, &pgn_move.comments);
+            needs_number = true;
+        }
+
+        for variation in &pgn_move.variations {
+            push_variation(words, variation, move_number, side_to_move);
+            needs_number = true;
+        }
+
+        if side_to_move == Players::Black {
+            move_number += 1;
+            side_to_move = Players::White;
+        } else {
+            side_to_move = Players::Black;
+        }
+    }
+}
+
+fn push_variation(
+    words: &mut Vec<String>,
+    variation: &PgnVariation,
+    move_number: u32,
+    side_to_move: Players,
+) {
+    let mut variation_words = Vec::new();
+
+    push_comments(&mut variation_words, &variation.initial_comments);
+    push_moves(
+        &mut variation_words,
+        &variation.moves,
+        move_number,
+        side_to_move,
+    );
+
+    if variation_words.is_empty() {
+        return;
+    }
+
+    variation_words[0].insert(0, '(');
+    variation_words
+        .last_mut()
+        .expect("Checked not empty")
+        .push(')');
+
+    words.append(&mut variation_words);
+}
+
+/*
+ * Comments are split into words so long comments can be wrapped like the rest of the movetext.
+ */
+fn push_comments(words: &mut Vec<String>, comments: &[String]) {
+    for comment in comments {
+        let mut comment_words: Vec<String> = comment
+            .replace('}', ")")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+
+        if comment_words.is_empty() {
+            words.push(String::from("{}"));
+            continue;
+        }
+
+        comment_words[0].insert(0, '{');
+        comment_words
+            .last_mut()
+            .expect("Checked not empty")
+            .push('}');
+
+        words.append(&mut comment_words);
+    }
+}
+
+fn wrap_words(words: &[String]) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+
+    for word in words {
+        if line_length > 0 && line_length + 1 + word.len() > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        }
+
+        if line_length > 0 {
+            text.push(' ');
+            line_length += 1;
+        }
+
+        text.push_str(word);
+        line_length += word.len();
+    }
+
+    text
+}
@@ -0,0 +1,239 @@
+use crate::chessboard::{ChessBoard, WKING, WPAWN, piece_to_char};
+use crate::engine::ChessEngine;
+use crate::fen::parsing::{parse_file, parse_rank};
+use crate::moves::{
+    LegalMove, LegalMoveParseError, LegalMoveParseErrorKind, MoveType, char_to_promotion,
+    file_to_char, parse_square, square_to_string,
+};
+
+impl ChessBoard {
+    /*
+     * Output a legal move of this position in SAN (Standard Algebraic Notation), e.g. Nbd7, exd5, O-O-O or e8=Q+.
+     */
+    pub fn move_to_san(&self, legal_move: LegalMove) -> String {
+        let mut san = match legal_move.move_type {
+            MoveType::CastleKingSide => String::from("O-O"),
+            MoveType::CastleQueenSide => String::from("O-O-O"),
+            _ => self.piece_move_to_san(legal_move),
+        };
+
+        let mut new_board = self.clone();
+        new_board.make_move(legal_move);
+
+        if new_board.is_checked() {
+            if new_board.legal_moves().is_empty() {
+                san.push('#');
+            } else {
+                san.push('+');
+            }
+        }
+
+        san
+    }
+
+    fn piece_move_to_san(&self, legal_move: LegalMove) -> String {
+        let piece = self.board[legal_move.from].abs();
+        let mut san = String::with_capacity(7);
+
+        if piece == WPAWN {
+            if legal_move.is_capture {
+                san.push(file_to_char(legal_move.from % 8));
+            }
+        } else {
+            san.push(piece_to_char(piece));
+            san.push_str(&self.disambiguation(legal_move));
+        }
+
+        if legal_move.is_capture {
+            san.push('x');
+        }
+
+        san.push_str(&square_to_string(legal_move.to));
+
+        if let MoveType::PawnMove {
+            promotion_move: Some(promotion),
+        } = legal_move.move_type
+        {
+            san.push('=');
+            san.push(piece_to_char(promotion.abs()));
+        }
+
+        san
+    }
+
+    /*
+     * When several pieces of the same type can reach the square, prefer the file, then the rank and lastly both.
+     */
+    fn disambiguation(&self, legal_move: LegalMove) -> String {
+        let piece = self.board[legal_move.from];
+
+        let rivals: Vec<usize> = self
+            .legal_moves()
+            .iter()
+            .filter(|other| {
+                other.to == legal_move.to
+                    && other.from != legal_move.from
+                    && self.board[other.from] == piece
+            })
+            .map(|other| other.from)
+            .collect();
+
+        if rivals.is_empty() {
+            return String::new();
+        }
+
+        let from = square_to_string(legal_move.from);
+
+        if rivals.iter().all(|rival| rival % 8 != legal_move.from % 8) {
+            from[..1].to_string()
+        } else if rivals.iter().all(|rival| rival / 8 != legal_move.from / 8) {
+            from[1..].to_string()
+        } else {
+            from
+        }
+    }
+
+    /*
+     * Resolve a SAN string against the legal moves of this position.
+     * Accepts 0-0 for castling, a missing or superfluous check suffix, annotations like ! and ?,
+     * and promotions written with or without '='.
+     */
+    pub fn parse_san_move(&self, san_move: &str) -> Result<LegalMove, LegalMoveParseError> {
+        let error = |kind| LegalMoveParseError::new(kind, san_move.to_string());
+
+        let san = san_move.trim_end_matches(['+', '#', '!', '?']);
+
+        let castle = match san {
+            "O-O" | "0-0" => Some(MoveType::CastleKingSide),
+            "O-O-O" | "0-0-0" => Some(MoveType::CastleQueenSide),
+            _ => None,
+        };
+
+        if let Some(castle) = castle {
+            return self
+                .legal_moves()
+                .into_iter()
+                .find(|legal_move| legal_move.move_type == castle)
+                .ok_or_else(|| error(LegalMoveParseErrorKind::Illegal));
+        }
+
+        let parsed =
+            parse_san_parts(san).ok_or_else(|| error(LegalMoveParseErrorKind::Malformed))?;
+
+        let candidates: Vec<LegalMove> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|legal_move| {
+                let from_file = legal_move.from % 8;
+                let from_rank = legal_move.from / 8;
+
+                legal_move.to == parsed.to
+                    && self.board[legal_move.from].abs() == parsed.piece
+                    && !matches!(
+                        legal_move.move_type,
+                        MoveType::CastleKingSide | MoveType::CastleQueenSide
+                    )
+                    && parsed.from_file.is_none_or(|file| file == from_file)
+                    && parsed.from_rank.is_none_or(|rank| rank == from_rank)
+            })
+            .collect();
+
+        let is_promotion = candidates.iter().any(|legal_move| {
+            matches!(
+                legal_move.move_type,
+                MoveType::PawnMove {
+                    promotion_move: Some(_)
+                }
+            )
+        });
+
+        if is_promotion && parsed.promotion.is_none() {
+            return Err(error(LegalMoveParseErrorKind::MissingPromotion));
+        }
+
+        let mut matching = candidates.into_iter().filter(|legal_move| {
+            let promotion = match legal_move.move_type {
+                MoveType::PawnMove {
+                    promotion_move: Some(promotion),
+                } => Some(promotion.abs()),
+                _ => None,
+            };
+            promotion == parsed.promotion
+        });
+
+        match (matching.next(), matching.next()) {
+            (Some(legal_move), None) => Ok(legal_move),
+            (None, _) => Err(error(LegalMoveParseErrorKind::Illegal)),
+            (Some(_), Some(_)) => Err(error(LegalMoveParseErrorKind::Ambiguous)),
+        }
+    }
+}
+
+struct SanParts {
+    piece: i8, /* Always the white piece, i.e. the absolute value */
+    from_file: Option<usize>,
+    from_rank: Option<usize>,
+    to: usize,
+    promotion: Option<i8>,
+}
+
+fn parse_san_parts(san: &str) -> Option<SanParts> {
+    let mut chars: Vec<char> = san.chars().collect();
+
+    let piece = match chars.first()? {
+        'K' => WKING,
+        'N' | 'B' | 'R' | 'Q' => {
+            char_to_promotion(chars[0].to_ascii_lowercase(), true).expect("Is a piece char")
+        }
+        _ => WPAWN,
+    };
+
+    if piece != WPAWN {
+        chars.remove(0);
+    }
+
+    let mut promotion = None;
+
+    if piece == WPAWN
+        && let Some(last) = chars.last()
+        && last.is_alphabetic()
+    {
+        promotion = Some(char_to_promotion(last.to_ascii_lowercase(), true)?);
+        chars.pop();
+
+        if chars.last() == Some(&'=') {
+            chars.pop();
+        }
+    }
+
+    if chars.len() < 2 {
+        return None;
+    }
+
+    let to_rank = chars.pop()?;
+    let to_file = chars.pop()?;
+    let to = parse_square(to_file, to_rank)?;
+
+    if chars.last() == Some(&'x') {
+        chars.pop();
+    }
+
+    let (from_file, from_rank) = match chars.as_slice() {
+        [] => (None, None),
+        [file, rank] => (Some(parse_file(*file)?), Some(parse_rank(*rank)?)),
+        [char] => match (parse_file(*char), parse_rank(*char)) {
+            (Some(file), _) => (Some(file), None),
+            (_, Some(rank)) => (None, Some(rank)),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(SanParts {
+        piece,
+        from_file,
+        from_rank,
+        to,
+        promotion,
+    })
+}
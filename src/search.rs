@@ -0,0 +1,715 @@
+use crate::bitboard::Bitboards;
+use crate::chessboard::{ChessBoard, Players, WKING, WPAWN};
+use crate::engine::exchange::{captured_piece, piece_value, promotion_value};
+use crate::engine::{ChessEngine, generate_legal_moves};
+use crate::evaluation::evaluate;
+use crate::moves::{LegalMove, MoveType, RatedMove};
+use crate::outcome::FIFTY_MOVE_RULE_HALF_MOVES;
+use crate::transposition::{Bound, TranspositionTable};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/* Scores are in centipawns, a mate in n plies scores MATE_SCORE - n. */
+pub const MATE_SCORE: i32 = 100_000;
+pub const INFINITY: i32 = 1_000_000;
+pub const MAX_DEPTH: u32 = 64;
+
+/* How many nodes are searched between checks of the clock and the stop flag. */
+const CHECK_INTERVAL: u64 = 1024;
+
+/* Move ordering ratings: the first move, then captures and promotions, then killer moves, then quiet moves. */
+const FIRST_MOVE_RATING: i32 = i32::MAX;
+const CAPTURE_RATING: i32 = 2_000_000;
+const KILLER_RATING: i32 = 1_000_000;
+/* History scores are halved once one passes this, so quiet moves always rate below the killer moves. */
+const MAX_HISTORY: i32 = 500_000;
+
+const NULL_MOVE_MIN_DEPTH: u32 = 3;
+const LATE_MOVE_MIN_DEPTH: u32 = 3;
+/* The number of moves searched at full depth before late move reductions start. */
+const LATE_MOVE_MIN_INDEX: usize = 3;
+const ASPIRATION_MIN_DEPTH: u32 = 4;
+const ASPIRATION_WINDOW: i32 = 50;
+/* Quiescence search stops at this ply even if captures are left. */
+const MAX_PLY: u32 = 2 * MAX_DEPTH;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    pub stop: Option<Arc<AtomicBool>>, /* Set from another thread to abort the search */
+    pub features: SearchFeatures,
+    pub history: Vec<u64>, /* Zobrist keys of the game positions before the searched one, oldest first */
+}
+
+/*
+ * The search techniques on top of plain alpha-beta, all enabled by default. Switching them off one by one makes it
+ * possible to test each of them and to measure what it is worth.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchFeatures {
+    pub quiescence: bool, /* Search captures and promotions at the horizon instead of evaluating */
+    pub see_pruning: bool, /* Skip captures that lose material in the quiescence search */
+    pub mvv_lva: bool,    /* Order captures by most valuable victim, then least valuable attacker */
+    pub killer_moves: bool, /* Try quiet moves that caused a cutoff at the same ply early */
+    pub history: bool,    /* Order quiet moves by how often they caused cutoffs */
+    pub null_move_pruning: bool,
+    pub late_move_reductions: bool,
+    pub aspiration_windows: bool,
+}
+
+impl SearchFeatures {
+    pub const fn none() -> Self {
+        Self {
+            quiescence: false,
+            see_pruning: false,
+            mvv_lva: false,
+            killer_moves: false,
+            history: false,
+            null_move_pruning: false,
+            late_move_reductions: false,
+            aspiration_windows: false,
+        }
+    }
+}
+
+impl Default for SearchFeatures {
+    fn default() -> Self {
+        Self {
+            quiescence: true,
+            see_pruning: true,
+            mvv_lva: true,
+            killer_moves: true,
+            history: true,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            aspiration_windows: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<LegalMove>,
+    pub score: i32, /* From the point of view of the side to move */
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub principal_variation: Vec<LegalMove>,
+}
+
+impl SearchResult {
+    /*
+     * The number of moves until mate as UCI reports it, negative if the side to move is getting mated.
+     */
+    pub fn mate_in(&self) -> Option<i32> {
+        if !is_mate_score(self.score) {
+            return None;
+        }
+
+        let plies = MATE_SCORE - self.score.abs();
+        let moves = (plies + 1) / 2;
+
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+}
+
+/* Mates are found up to MAX_PLY plies deep, as the quiescence search goes past MAX_DEPTH when in check. */
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE - MAX_PLY as i32
+}
+
+/*
+ * Iterative deepening over a negamax alpha-beta search. report is called after every completed depth.
+ * The result of the deepest completed iteration is returned, a partial iteration is only used if not even
+ * the first depth finished. The table may hold entries of earlier searches, which are reused.
+ * With aspiration windows, deeper iterations start with a narrow window around the previous score and widen it
+ * whenever the score falls outside.
+ */
+pub fn iterative_deepening<F>(
+    chessboard: &ChessBoard,
+    limits: &SearchLimits,
+    table: &TranspositionTable,
+    mut report: F,
+) -> SearchResult
+where
+    F: FnMut(&SearchResult),
+{
+    table.new_search();
+
+    let mut searcher = Searcher::new(limits, table);
+    let mut board = chessboard.clone();
+
+    let mut result = SearchResult {
+        best_move: chessboard.legal_moves().first().copied(),
+        score: 0,
+        depth: 0,
+        nodes: 0,
+        elapsed: Duration::ZERO,
+        principal_variation: Vec::new(),
+    };
+
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+    for depth in 1..=max_depth {
+        let mut principal_variation = Vec::new();
+        let mut window = ASPIRATION_WINDOW;
+
+        let (mut alpha, mut beta) = if limits.features.aspiration_windows
+            && depth >= ASPIRATION_MIN_DEPTH
+            && !is_mate_score(result.score)
+        {
+            (result.score - window, result.score + window)
+        } else {
+            (-INFINITY, INFINITY)
+        };
+
+        let score = loop {
+            principal_variation.clear();
+            searcher.previous_variation = result.principal_variation.clone();
+            searcher.follow_variation = true;
+
+            let score =
+                searcher.negamax(&mut board, depth, 0, alpha, beta, &mut principal_variation);
+
+            if searcher.aborted {
+                break score;
+            }
+
+            if score <= alpha && alpha > -INFINITY {
+                alpha = (alpha - window).max(-INFINITY);
+            } else if score >= beta && beta < INFINITY {
+                beta = (beta + window).min(INFINITY);
+            } else {
+                break score;
+            }
+
+            window *= 2;
+        };
+
+        if searcher.aborted && (depth > 1 || principal_variation.is_empty()) {
+            break;
+        }
+
+        result = SearchResult {
+            best_move: principal_variation.first().copied().or(result.best_move),
+            score,
+            depth,
+            nodes: searcher.nodes,
+            elapsed: searcher.start.elapsed(),
+            principal_variation,
+        };
+
+        report(&result);
+
+        // No point in searching deeper than the mate that was found.
+        let mate_found =
+            is_mate_score(result.score) && MATE_SCORE - result.score.abs() <= depth as i32;
+
+        if searcher.aborted || mate_found || result.best_move.is_none() {
+            break;
+        }
+    }
+
+    result.nodes = searcher.nodes;
+    result.elapsed = searcher.start.elapsed();
+    result
+}
+
+struct Searcher<'a> {
+    limits: &'a SearchLimits,
+    features: SearchFeatures,
+    table: &'a TranspositionTable,
+    start: Instant,
+    nodes: u64,
+    aborted: bool,
+    previous_variation: Vec<LegalMove>, /* The best line of the previous iteration */
+    follow_variation: bool, /* Whether the current node lies on the previous best line */
+    after_null_move: bool,  /* Whether the move into the current node was a null move */
+    killer_moves: [[Option<LegalMove>; 2]; MAX_DEPTH as usize + 1], /* Indexed by ply */
+    history: [[i32; 64]; 64], /* Indexed by the from and to square of quiet moves */
+    path: Vec<u64>, /* Zobrist keys of the game positions and the positions searched on the way to this node */
+}
+
+impl<'a> Searcher<'a> {
+    fn new(limits: &'a SearchLimits, table: &'a TranspositionTable) -> Self {
+        Self {
+            limits,
+            features: limits.features,
+            table,
+            start: Instant::now(),
+            nodes: 0,
+            aborted: false,
+            previous_variation: Vec::new(),
+            follow_variation: false,
+            after_null_move: false,
+            killer_moves: [[None; 2]; MAX_DEPTH as usize + 1],
+            history: [[0; 64]; 64],
+            path: limits.history.clone(),
+        }
+    }
+
+    /*
+     * Whether the position occurred before in the game or on the current line. Only positions since the last
+     * capture or pawn move can match, and only every second one has the same side to move. A single repetition
+     * already scores as a draw: if repeating is good for one side, it can repeat again.
+     */
+    fn is_repetition(&self, chessboard: &ChessBoard) -> bool {
+        self.path
+            .iter()
+            .rev()
+            .take(chessboard.half_move_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|key| *key == chessboard.zobrist_key)
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.aborted = true;
+        } else if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            let out_of_time = self
+                .limits
+                .time
+                .is_some_and(|time| self.start.elapsed() >= time);
+
+            let stopped = self
+                .limits
+                .stop
+                .as_ref()
+                .is_some_and(|stop| stop.load(Ordering::Relaxed));
+
+            self.aborted = out_of_time || stopped;
+        }
+
+        self.aborted
+    }
+
+    /*
+     * principal_variation is filled with the best line found from this node.
+     * While the search follows the best line of the previous iteration, its move is tried first.
+     */
+    fn negamax(
+        &mut self,
+        chessboard: &mut ChessBoard,
+        depth: u32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+        principal_variation: &mut Vec<LegalMove>,
+    ) -> i32 {
+        let after_null_move = std::mem::take(&mut self.after_null_move);
+
+        if ply > 0 && self.should_stop() {
+            return 0;
+        }
+
+        if ply > 0 && self.is_repetition(chessboard) {
+            return 0;
+        }
+
+        if depth == 0 && self.features.quiescence {
+            return self.quiescence(chessboard, ply, alpha, beta);
+        }
+
+        self.nodes += 1;
+
+        let legal_moves = generate_legal_moves(chessboard);
+        let in_check = chessboard.is_checked();
+
+        if legal_moves.is_empty() {
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        if ply > 0 && chessboard.half_move_clock >= FIFTY_MOVE_RULE_HALF_MOVES {
+            return 0;
+        }
+
+        if depth == 0 {
+            return evaluate(chessboard);
+        }
+
+        let key = chessboard.zobrist_key;
+        let entry = self.table.probe(key);
+
+        // The root always searches, so it has a move and a principal variation to report.
+        if let Some(entry) = entry
+            && ply > 0
+            && entry.depth >= depth
+        {
+            let score = score_from_table(entry.score, ply);
+
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+
+            if usable {
+                return score;
+            }
+        }
+
+        // If passing the turn still fails high, a real move will too. Zugzwang makes this unsound, so it is
+        // skipped when the side to move only has pawns left.
+        if self.features.null_move_pruning
+            && ply > 0
+            && depth >= NULL_MOVE_MIN_DEPTH
+            && !after_null_move
+            && !in_check
+            && !self.follow_variation
+            && !is_mate_score(beta)
+            && has_pieces(&chessboard.bitboards, chessboard.side_to_move)
+            && evaluate(chessboard) >= beta
+        {
+            let reduction = if depth > 6 { 3 } else { 2 };
+
+            self.path.push(key);
+            let saved_state = chessboard.make_null_move();
+
+            self.after_null_move = true;
+
+            let score = -self.negamax(
+                chessboard,
+                depth.saturating_sub(1 + reduction),
+                ply + 1,
+                -beta,
+                -beta + 1,
+                &mut Vec::new(),
+            );
+
+            chessboard.unmake_null_move(saved_state);
+            self.path.pop();
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+        }
+
+        let variation_move = if self.follow_variation {
+            self.previous_variation.get(ply as usize).copied()
+        } else {
+            None
+        };
+
+        self.follow_variation = variation_move.is_some();
+
+        let table_move = entry.and_then(|entry| entry.best_move(&legal_moves));
+        let rated_moves =
+            self.order_moves(chessboard, legal_moves, variation_move.or(table_move), ply);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+
+        for (index, rated_move) in rated_moves.into_iter().enumerate() {
+            let legal_move = rated_move.chess_move;
+
+            // Only the first move, the one from the previous best line, keeps following it.
+            if index > 0 {
+                self.follow_variation = false;
+            }
+
+            self.path.push(key);
+            let saved_state = chessboard.make_move(legal_move);
+
+            let mut child_principal_variation = Vec::new();
+
+            // Late quiet moves are unlikely to be best, so they get a shallower null window search first and
+            // only a full one if they beat alpha.
+            let reduce = self.features.late_move_reductions
+                && depth >= LATE_MOVE_MIN_DEPTH
+                && index >= LATE_MOVE_MIN_INDEX
+                && !in_check
+                && is_quiet(&legal_move)
+                && rated_move.rating < KILLER_RATING - 1
+                && !chessboard.is_checked();
+
+            let mut score = alpha + 1;
+
+            if reduce {
+                let reduction = if depth >= 6 && index >= 8 { 2 } else { 1 };
+
+                score = -self.negamax(
+                    chessboard,
+                    depth - 1 - reduction,
+                    ply + 1,
+                    -alpha - 1,
+                    -alpha,
+                    &mut child_principal_variation,
+                );
+            }
+
+            if score > alpha && !self.aborted {
+                child_principal_variation.clear();
+
+                score = -self.negamax(
+                    chessboard,
+                    depth - 1,
+                    ply + 1,
+                    -beta,
+                    -alpha,
+                    &mut child_principal_variation,
+                );
+            }
+
+            chessboard.unmake_move(legal_move, saved_state);
+            self.path.pop();
+
+            if self.aborted {
+                // The root keeps the moves it finished, so a partial first iteration still has a move.
+                return if ply == 0 { best_score } else { 0 };
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(legal_move);
+
+                if score > alpha {
+                    alpha = score;
+
+                    principal_variation.clear();
+                    principal_variation.push(legal_move);
+                    principal_variation.append(&mut child_principal_variation);
+                }
+            }
+
+            if alpha >= beta {
+                if is_quiet(&legal_move) {
+                    self.update_quiet_cutoff(legal_move, depth, ply);
+                }
+
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        self.table.store(
+            key,
+            depth,
+            score_to_table(best_score, ply),
+            bound,
+            best_move,
+        );
+
+        best_score
+    }
+
+    /*
+     * Search captures and promotions until the position is quiet, so the evaluation is not taken in the middle of
+     * an exchange. The side to move may stand pat on the evaluation instead of capturing, unless it is in check,
+     * then all moves are searched.
+     */
+    fn quiescence(
+        &mut self,
+        chessboard: &mut ChessBoard,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        let legal_moves = generate_legal_moves(chessboard);
+        let in_check = chessboard.is_checked();
+
+        if legal_moves.is_empty() {
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        if ply >= MAX_PLY {
+            return evaluate(chessboard);
+        }
+
+        let mut best_score = -INFINITY;
+
+        if !in_check {
+            best_score = evaluate(chessboard);
+
+            if best_score >= beta {
+                return best_score;
+            }
+
+            alpha = alpha.max(best_score);
+        }
+
+        let forcing_moves = legal_moves
+            .into_iter()
+            .filter(|legal_move| in_check || !is_quiet(legal_move))
+            .filter(|legal_move| {
+                in_check
+                    || !self.features.see_pruning
+                    || !legal_move.is_capture
+                    || chessboard.see(*legal_move) >= 0
+            })
+            .collect();
+
+        for rated_move in self.order_moves(chessboard, forcing_moves, None, ply) {
+            let legal_move = rated_move.chess_move;
+            let saved_state = chessboard.make_move(legal_move);
+
+            let score = -self.quiescence(chessboard, ply + 1, -beta, -alpha);
+
+            chessboard.unmake_move(legal_move, saved_state);
+
+            if self.aborted {
+                return 0;
+            }
+
+            best_score = best_score.max(score);
+            alpha = alpha.max(score);
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best_score
+    }
+
+    /*
+     * Rate the moves and sort them best first: first_move, captures and promotions, killer moves, then quiet moves
+     * by their history score.
+     */
+    fn order_moves(
+        &self,
+        chessboard: &ChessBoard,
+        legal_moves: Vec<LegalMove>,
+        first_move: Option<LegalMove>,
+        ply: u32,
+    ) -> Vec<RatedMove> {
+        let killer_moves = self
+            .killer_moves
+            .get(ply as usize)
+            .copied()
+            .unwrap_or_default();
+
+        let mut rated_moves: Vec<RatedMove> = legal_moves
+            .into_iter()
+            .map(|legal_move| {
+                let rating = if Some(legal_move) == first_move {
+                    FIRST_MOVE_RATING
+                } else if !is_quiet(&legal_move) {
+                    CAPTURE_RATING
+                        + if self.features.mvv_lva {
+                            mvv_lva(chessboard, &legal_move)
+                        } else {
+                            0
+                        }
+                } else if self.features.killer_moves && killer_moves[0] == Some(legal_move) {
+                    KILLER_RATING
+                } else if self.features.killer_moves && killer_moves[1] == Some(legal_move) {
+                    KILLER_RATING - 1
+                } else if self.features.history {
+                    self.history[legal_move.from][legal_move.to]
+                } else {
+                    0
+                };
+
+                RatedMove {
+                    chess_move: legal_move,
+                    rating,
+                }
+            })
+            .collect();
+
+        rated_moves.sort_by_key(|rated_move| std::cmp::Reverse(rated_move.rating));
+        rated_moves
+    }
+
+    fn update_quiet_cutoff(&mut self, legal_move: LegalMove, depth: u32, ply: u32) {
+        if self.features.killer_moves
+            && let Some(killer_moves) = self.killer_moves.get_mut(ply as usize)
+            && killer_moves[0] != Some(legal_move)
+        {
+            killer_moves[1] = killer_moves[0];
+            killer_moves[0] = Some(legal_move);
+        }
+
+        if self.features.history {
+            let score = &mut self.history[legal_move.from][legal_move.to];
+            *score += (depth * depth) as i32;
+
+            if *score > MAX_HISTORY {
+                self.history
+                    .iter_mut()
+                    .flatten()
+                    .for_each(|score| *score /= 2);
+            }
+        }
+    }
+}
+
+/*
+ * Mate scores count plies from the root, the table stores them counted from the position itself so they stay
+ * right when the position is reached at another ply.
+ */
+fn score_to_table(score: i32, ply: u32) -> i32 {
+    if is_mate_score(score) {
+        score + score.signum() * ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: u32) -> i32 {
+    if is_mate_score(score) {
+        score - score.signum() * ply as i32
+    } else {
+        score
+    }
+}
+
+fn is_quiet(legal_move: &LegalMove) -> bool {
+    !legal_move.is_capture
+        && !matches!(
+            legal_move.move_type,
+            MoveType::PawnMove {
+                promotion_move: Some(_)
+            }
+        )
+}
+
+/*
+ * Most valuable victim, least valuable attacker: a pawn taking a queen is tried before a queen taking a pawn.
+ */
+fn mvv_lva(chessboard: &ChessBoard, legal_move: &LegalMove) -> i32 {
+    let victim = piece_value(captured_piece(chessboard, legal_move)) + promotion_value(legal_move);
+    let attacker = piece_value(chessboard.board[legal_move.from]);
+
+    victim * 16 - attacker / 16
+}
+
+fn has_pieces(bitboards: &Bitboards, side: Players) -> bool {
+    let pawns_and_king = bitboards.pieces(side, WPAWN) | bitboards.pieces(side, WKING);
+
+    bitboards.colour(side) & !pawns_and_king != 0
+}
@@ -1,6 +1,33 @@
+mod bitboard_test;
+mod board_canvas_test;
+mod chess960_test;
+mod chess_game_test;
+mod clock_test;
+mod evaluation_test;
 mod fen_test;
 mod king_is_checked;
+mod legal_move_generation_test;
 mod legal_move_test;
 mod mailbox_test;
+mod make_unmake_test;
+mod outcome_test;
+mod perft_command_test;
 mod perft_test;
+mod pgn_test;
+mod san_test;
+mod search_test;
+mod see_test;
 mod square_color_from_pos;
+mod transposition_test;
+mod uci_move_test;
+mod uci_test;
+mod zobrist_test;
+
+#[cfg(test)]
+use crate::{chessboard::ChessBoard, fen::Fen};
+
+/* The position of a FEN string the test knows to be valid. */
+#[cfg(test)]
+pub fn board_from(fen: &str) -> ChessBoard {
+    ChessBoard::set_fen_position(fen).expect("Is valid FEN")
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::{Bitboards, square_bit, squares},
+        chessboard::{BPAWN, ChessBoard, Players, WKNIGHT, WPAWN},
+        engine::attacks::{
+            KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS, bishop_attacks, bishop_ray_attacks,
+            queen_attacks, rook_attacks, rook_ray_attacks,
+        },
+    };
+
+    fn bitboard_of(positions: &[usize]) -> u64 {
+        positions
+            .iter()
+            .fold(0, |bitboard, position| bitboard | square_bit(*position))
+    }
+
+    #[test]
+    fn test_squares_iterates_in_order() {
+        let positions: Vec<usize> = squares(bitboard_of(&[63, 0, 27])).collect();
+
+        assert_eq!(positions, vec![0, 27, 63]);
+    }
+
+    #[test]
+    fn test_step_attacks() {
+        // Knight on a1 reaches b3 and c2.
+        assert_eq!(KNIGHT_ATTACKS[0], bitboard_of(&[17, 10]));
+        // King on h8 reaches g8, g7 and h7.
+        assert_eq!(KING_ATTACKS[63], bitboard_of(&[62, 54, 55]));
+        // Pawns on the a-file only attack towards the b-file.
+        assert_eq!(PAWN_ATTACKS[0][8], bitboard_of(&[17]));
+        assert_eq!(PAWN_ATTACKS[1][52], bitboard_of(&[43, 45]));
+    }
+
+    #[test]
+    fn test_sliding_attacks_stop_at_blockers() {
+        // Rook on d4 with blockers on d6 and b4, the blockers themselves are attacked.
+        let occupied = bitboard_of(&[27, 43, 25]);
+
+        assert_eq!(
+            rook_attacks(27, occupied),
+            bitboard_of(&[35, 43, 19, 11, 3, 28, 29, 30, 31, 26, 25])
+        );
+
+        // Bishop on a1 on an empty board sees the whole long diagonal.
+        assert_eq!(
+            bishop_attacks(0, 0),
+            bitboard_of(&[9, 18, 27, 36, 45, 54, 63])
+        );
+
+        assert_eq!(
+            queen_attacks(27, occupied),
+            rook_attacks(27, occupied) | bishop_attacks(27, occupied)
+        );
+    }
+
+    #[test]
+    fn test_bitboards_from_start_position() {
+        let bitboards = ChessBoard::default().bitboards;
+
+        assert_eq!(bitboards.pieces(Players::White, WPAWN), 0xff00);
+        assert_eq!(bitboards.pieces(Players::Black, -BPAWN), 0xff << 48);
+        assert_eq!(
+            bitboards.pieces(Players::White, WKNIGHT),
+            bitboard_of(&[1, 6])
+        );
+        assert_eq!(bitboards.colour(Players::White), 0xffff);
+        assert_eq!(bitboards.occupied, 0xffff | (0xffff << 48));
+    }
+
+    #[test]
+    fn test_set_square_keeps_bitboards_in_sync() {
+        let mut chessboard = ChessBoard::default();
+
+        chessboard.set_square(28, WKNIGHT);
+        chessboard.set_square(1, BPAWN);
+        chessboard.set_square(6, 0);
+
+        assert_eq!(
+            chessboard.bitboards,
+            Bitboards::from_board(&chessboard.board)
+        );
+    }
+
+    #[test]
+    fn test_magic_attacks_match_ray_attacks() {
+        // Sparse pseudo random occupancies from a fixed xorshift sequence.
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..200 {
+            let occupied = next() & next();
+
+            for position in 0..64 {
+                assert_eq!(
+                    bishop_attacks(position, occupied),
+                    bishop_ray_attacks(position, occupied)
+                );
+                assert_eq!(
+                    rook_attacks(position, occupied),
+                    rook_ray_attacks(position, occupied)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use iced::mouse::{self, Button, Cursor};
+    use iced::widget::canvas::{Action, Event, Program};
+    use iced::{Point, Rectangle, Size};
+
+    use crate::{
+        bitboard::square_bit,
+        board_canvas::{BoardCanvas, BoardState, square_at},
+        chess_game::{Message, SquareHighlight, SvgPieces},
+        chessboard::{ChessBoard, Players},
+    };
+
+    /* A board of 800 pixels, so every square is 100 pixels wide. */
+    const BOARD: Size = Size::new(800., 800.);
+    const E2: usize = 12;
+    const E4: usize = 28;
+
+    fn bounds() -> Rectangle {
+        Rectangle::new(Point::ORIGIN, BOARD)
+    }
+
+    /* The middle of a square shown with white at the bottom. */
+    fn centre(square: usize) -> Point {
+        Point::new(
+            (square % 8) as f32 * 100. + 50.,
+            (7 - square / 8) as f32 * 100. + 50.,
+        )
+    }
+
+    fn send(
+        canvas: &BoardCanvas,
+        state: &mut BoardState,
+        event: mouse::Event,
+        point: Point,
+    ) -> Option<Message> {
+        canvas
+            .update(
+                state,
+                &Event::Mouse(event),
+                bounds(),
+                Cursor::Available(point),
+            )
+            .and_then(|action: Action<Message>| action.into_inner().0)
+    }
+
+    #[test]
+    fn test_square_at() {
+        assert_eq!(square_at(centre(E2), BOARD, Players::White), Some(E2));
+        assert_eq!(
+            square_at(Point::new(0., 0.), BOARD, Players::White),
+            Some(56)
+        );
+        assert_eq!(
+            square_at(Point::new(0., 0.), BOARD, Players::Black),
+            Some(7)
+        );
+        assert_eq!(
+            square_at(Point::new(799., 799.), BOARD, Players::Black),
+            Some(56)
+        );
+
+        // Only the square part of a wider canvas is board.
+        assert_eq!(
+            square_at(
+                Point::new(850., 50.),
+                Size::new(1000., 800.),
+                Players::White
+            ),
+            None
+        );
+        assert_eq!(square_at(Point::new(-1., 50.), BOARD, Players::White), None);
+    }
+
+    #[test]
+    fn test_drag_and_click() {
+        let board = ChessBoard::default();
+        let pieces = SvgPieces::default();
+        let mut canvas = BoardCanvas {
+            board: &board.board,
+            highlights: [SquareHighlight::default(); 64],
+            movable: square_bit(E2),
+            perspective: Players::White,
+            pieces: &pieces,
+            can_update: true,
+        };
+        let mut state = BoardState::default();
+
+        // A press selects the piece and starts a drag, dropping it on another square asks for the move.
+        let pressed = send(
+            &canvas,
+            &mut state,
+            mouse::Event::ButtonPressed(Button::Left),
+            centre(E2),
+        );
+
+        assert!(matches!(pressed, Some(Message::ClickedSquare(E2))));
+
+        let moved = mouse::Event::CursorMoved {
+            position: centre(E4),
+        };
+
+        assert!(send(&canvas, &mut state, moved, centre(E4)).is_none());
+
+        let released = send(
+            &canvas,
+            &mut state,
+            mouse::Event::ButtonReleased(Button::Left),
+            centre(E4),
+        );
+
+        assert!(matches!(released, Some(Message::DroppedPiece(E2, E4))));
+
+        // Pressing and releasing the selected piece without moving unselects it.
+        canvas.highlights[E2].selected = true;
+
+        assert!(
+            send(
+                &canvas,
+                &mut state,
+                mouse::Event::ButtonPressed(Button::Left),
+                centre(E2)
+            )
+            .is_none()
+        );
+
+        let released = send(
+            &canvas,
+            &mut state,
+            mouse::Event::ButtonReleased(Button::Left),
+            centre(E2),
+        );
+
+        assert!(matches!(released, Some(Message::ClickedSquare(E2))));
+
+        // Nothing happens while the board is disabled.
+        canvas.can_update = false;
+
+        assert!(
+            send(
+                &canvas,
+                &mut state,
+                mouse::Event::ButtonPressed(Button::Left),
+                centre(E4)
+            )
+            .is_none()
+        );
+    }
+}
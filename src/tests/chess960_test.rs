@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess960::{CHESS960_POSITIONS, STANDARD_POSITION, chess960_back_rank, chess960_position},
+        chessboard::{ChessBoard, EMPTY, WBISHOP, WKING, WROOK},
+        fen::{FEN_STARTING_POSITION, Fen},
+        moves::MoveType,
+        perft::{parse_perft_suite, run_perft_suite},
+        tests::board_from,
+    };
+
+    /* The king castles king side without moving, only the rook goes from h1 to f1. */
+    const KING_ON_G1: &str = "1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R4KR w KQkq - 0 1";
+
+    #[test]
+    fn test_start_positions() {
+        assert_eq!(
+            chess960_position(STANDARD_POSITION).to_fen(),
+            FEN_STARTING_POSITION
+        );
+        assert_eq!(
+            chess960_position(0).to_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+
+        let mut back_ranks: Vec<[i8; 8]> =
+            (0..CHESS960_POSITIONS).map(chess960_back_rank).collect();
+
+        for back_rank in &back_ranks {
+            let files_of = |piece| (0..8).filter(move |file| back_rank[*file] == piece);
+            let bishops: Vec<usize> = files_of(WBISHOP).collect();
+            let rooks: Vec<usize> = files_of(WROOK).collect();
+            let king = files_of(WKING).next().expect("There is a king");
+
+            assert!(!back_rank.contains(&EMPTY));
+            assert_eq!(bishops[0] % 2 + bishops[1] % 2, 1);
+            assert!(rooks[0] < king && king < rooks[1]);
+        }
+
+        back_ranks.sort();
+        back_ranks.dedup();
+
+        assert_eq!(back_ranks.len(), CHESS960_POSITIONS);
+    }
+
+    #[test]
+    fn test_castling_rights_in_fen() {
+        let board = board_from("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+
+        assert_eq!(board.castling_ability, [Some(7), Some(5), Some(7), Some(5)]);
+        assert_eq!(
+            board.to_fen(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+
+        // The file is kept when the castling rook is not the outermost rook on its side of the king.
+        let inner_rook = "1k2r2r/8/8/8/8/8/8/1K2R2R w Eh - 0 1";
+        let board = board_from(inner_rook);
+
+        assert_eq!(board.castling_ability, [Some(4), None, Some(7), None]);
+        assert_eq!(board.to_fen(), "1k2r2r/8/8/8/8/8/8/1K2R2R w Ek - 0 1");
+        assert_eq!(board_from(&board.to_fen()), board);
+
+        // Rights without a rook to castle with are dropped.
+        assert_eq!(
+            board_from("4k3/8/8/8/8/8/8/4K2R w KQkq - 0 1").castling_ability,
+            [Some(7), None, None, None]
+        );
+
+        for invalid in ["KK", "KQA", "Ii", "K-"] {
+            assert!(
+                ChessBoard::set_fen_position(&format!("4k3/8/8/8/8/8/8/R3K2R w {invalid} - 0 1"))
+                    .is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_castle_without_moving_the_king() {
+        let mut board = board_from(KING_ON_G1);
+        let original = board.clone();
+
+        let castle = board
+            .parse_chess960_uci_move("g1h1")
+            .expect("Castling is legal");
+
+        assert_eq!(castle.move_type, MoveType::CastleKingSide);
+        assert_eq!((castle.from, castle.to), (6, 6));
+        assert_eq!(board.uci_move_string(castle, true), "g1h1");
+        assert_eq!(board.uci_move_string(castle, false), "g1g1");
+
+        // g1h1 is not a legal move in the standard notation, and f1 is reached by the king stepping there.
+        assert!(board.parse_uci_move("g1h1").is_err());
+        assert_eq!(
+            board.parse_chess960_uci_move("g1f1").map(|m| m.move_type),
+            Ok(MoveType::KingMove)
+        );
+
+        let saved_state = board.make_move(castle);
+
+        assert_eq!(
+            board.to_fen(),
+            "1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 b kq - 1 1"
+        );
+
+        board.unmake_move(castle, saved_state);
+
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn test_castling_rook_may_not_uncover_check() {
+        // After b1 goes to d1 the rook on a1 would attack the king on c1.
+        let board = board_from("k7/8/8/8/8/8/8/rRK5 w B - 0 1");
+
+        assert!(board.parse_chess960_uci_move("c1b1").is_err());
+
+        let board = board_from("k7/8/8/8/8/8/8/1RK5 w B - 0 1");
+        let castle = board
+            .parse_chess960_uci_move("c1b1")
+            .expect("Castling is legal");
+
+        assert_eq!(castle.move_type, MoveType::CastleQueenSide);
+    }
+
+    #[test]
+    fn test_rook_move_and_capture_remove_rights() {
+        let mut board = board_from("1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R4KR w KQkq - 0 1");
+
+        for uci_move in ["b1a1", "b8a8"] {
+            let legal_move = board.parse_uci_move(uci_move).expect("Is legal");
+            board.make_move(legal_move);
+        }
+
+        assert_eq!(board.castling_ability, [Some(7), None, Some(7), None]);
+        assert_eq!(
+            board.to_fen(),
+            "r5kr/pppppppp/8/8/8/8/PPPPPPPP/R5KR w Kk - 2 2"
+        );
+    }
+
+    #[test]
+    fn test_chess960_perft() {
+        let contents =
+            std::fs::read_to_string(format!("{}/perft/chess960.epd", env!("CARGO_MANIFEST_DIR")))
+                .expect("The suite file is readable");
+        let entries = parse_perft_suite(&contents).expect("The suite file is valid");
+
+        let mismatches = run_perft_suite(&entries, Some(3), 2, |_, _| {})
+            .expect("The suite positions are valid");
+
+        assert_eq!(mismatches, vec![]);
+    }
+}
@@ -0,0 +1,491 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess_game::{ChessGame, Message, MoveTarget, Navigation, SquareHighlight, Strength},
+        chessboard::{Players, WQUEEN},
+        clock::TimeControl,
+        outcome::GameOutcome,
+        pgn::PgnResult,
+    };
+    use std::time::{Duration, Instant};
+
+    /* Squares in ChessBoard::board order, a1 = 0. */
+    const A1: usize = 0;
+    const E2: usize = 12;
+    const E3: usize = 20;
+    const E4: usize = 28;
+    const F2: usize = 13;
+    const G1: usize = 6;
+    const F3: usize = 21;
+    const H3: usize = 23;
+
+    fn started_game() -> ChessGame {
+        let mut game = ChessGame::default();
+        _ = game.update(Message::Start);
+        game
+    }
+
+    fn click(game: &mut ChessGame, square: usize) {
+        _ = game.update(Message::ClickedSquare(square));
+    }
+
+    fn play(game: &mut ChessGame, moves: &[(usize, usize)]) {
+        for (from, to) in moves {
+            click(game, *from);
+            click(game, *to);
+        }
+    }
+
+    fn sans(game: &ChessGame) -> Vec<&str> {
+        game.moves()
+            .iter()
+            .map(|played| played.san.as_str())
+            .collect()
+    }
+
+    fn selected(highlights: &[SquareHighlight; 64]) -> Vec<usize> {
+        (0..64)
+            .filter(|square| highlights[*square].selected)
+            .collect()
+    }
+
+    #[test]
+    fn test_selected_piece_shows_its_moves() {
+        let mut game = started_game();
+
+        click(&mut game, E2);
+
+        let highlights = game.square_highlights();
+
+        assert_eq!(selected(&highlights), vec![E2]);
+        assert_eq!(highlights[E3].target, Some(MoveTarget::Quiet));
+        assert_eq!(highlights[E4].target, Some(MoveTarget::Quiet));
+        assert_eq!(highlights.iter().filter(|h| h.target.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn test_pieces_without_moves_are_not_selected() {
+        let mut game = started_game();
+
+        click(&mut game, A1);
+
+        assert!(selected(&game.square_highlights()).is_empty());
+
+        // Black pieces can not be selected while it is white's move.
+        click(&mut game, 52);
+
+        assert!(selected(&game.square_highlights()).is_empty());
+    }
+
+    #[test]
+    fn test_clicking_own_piece_reselects() {
+        let mut game = started_game();
+
+        click(&mut game, E2);
+        click(&mut game, G1);
+
+        let highlights = game.square_highlights();
+
+        assert_eq!(selected(&highlights), vec![G1]);
+        assert_eq!(highlights[F3].target, Some(MoveTarget::Quiet));
+        assert_eq!(highlights[H3].target, Some(MoveTarget::Quiet));
+
+        // A click that is not a move clears the selection.
+        click(&mut game, 36);
+
+        assert!(selected(&game.square_highlights()).is_empty());
+    }
+
+    #[test]
+    fn test_last_move_and_check() {
+        let mut game = started_game();
+
+        // 1. f3 e5 2. g4 Qh4+
+        for (from, to) in [(F2, F3), (52, 36), (14, 30), (59, 31)] {
+            click(&mut game, from);
+            click(&mut game, to);
+        }
+
+        let highlights = game.square_highlights();
+        let marked: Vec<usize> = (0..64)
+            .filter(|square| highlights[*square].last_move)
+            .collect();
+
+        assert_eq!(marked, vec![31, 59]);
+        assert!(highlights[4].checked_king);
+        assert_eq!(highlights.iter().filter(|h| h.checked_king).count(), 1);
+    }
+
+    #[test]
+    fn test_captures_are_marked() {
+        let mut game = started_game();
+
+        // 1. e4 d5, then the e4 pawn may take on d5.
+        for (from, to) in [(E2, E4), (51, 35)] {
+            click(&mut game, from);
+            click(&mut game, to);
+        }
+
+        click(&mut game, E4);
+
+        let highlights = game.square_highlights();
+
+        assert_eq!(highlights[35].target, Some(MoveTarget::Capture));
+        assert_eq!(highlights[36].target, Some(MoveTarget::Quiet));
+    }
+
+    #[test]
+    fn test_dropped_pieces() {
+        let mut game = started_game();
+
+        // An illegal drop leaves the board as it was.
+        _ = game.update(Message::DroppedPiece(E2, 36));
+
+        assert!(game.square_highlights().iter().all(|h| !h.last_move));
+        assert_ne!(game.movable_squares(), 0);
+
+        _ = game.update(Message::DroppedPiece(E2, E4));
+
+        let highlights = game.square_highlights();
+
+        assert!(highlights[E2].last_move && highlights[E4].last_move);
+        assert!(selected(&highlights).is_empty());
+    }
+
+    #[test]
+    fn test_moves_are_listed_in_san() {
+        let mut game = started_game();
+
+        // 1. e4 e5 2. Nf3
+        play(&mut game, &[(E2, E4), (52, 36), (G1, F3)]);
+
+        assert_eq!(sans(&game), vec!["e4", "e5", "Nf3"]);
+        assert_eq!(game.viewed_ply(), 3);
+    }
+
+    #[test]
+    fn test_earlier_positions_are_read_only() {
+        let mut game = started_game();
+
+        play(&mut game, &[(E2, E4), (52, 36)]);
+
+        _ = game.update(Message::Navigate(Navigation::Previous));
+
+        assert_eq!(game.viewed_ply(), 1);
+        assert_eq!(game.movable_squares(), 0);
+
+        // The board shows the position after 1. e4 with its move marked.
+        let highlights = game.square_highlights();
+
+        assert!(highlights[E2].last_move && highlights[E4].last_move);
+
+        click(&mut game, 51);
+        click(&mut game, 35);
+
+        assert_eq!(sans(&game), vec!["e4", "e5"]);
+
+        _ = game.update(Message::Navigate(Navigation::First));
+
+        assert_eq!(game.viewed_ply(), 0);
+        assert!(game.square_highlights().iter().all(|h| !h.last_move));
+
+        _ = game.update(Message::Navigate(Navigation::Previous));
+
+        assert_eq!(game.viewed_ply(), 0);
+
+        _ = game.update(Message::Navigate(Navigation::Ply(10)));
+
+        assert_eq!(game.viewed_ply(), 2);
+        assert_ne!(game.movable_squares(), 0);
+    }
+
+    #[test]
+    fn test_play_from_here() {
+        let mut game = started_game();
+
+        play(&mut game, &[(E2, E4), (52, 36), (G1, F3)]);
+
+        _ = game.update(Message::Navigate(Navigation::Ply(1)));
+        _ = game.update(Message::PlayFromHere);
+
+        assert_eq!(sans(&game), vec!["e4"]);
+        assert_eq!(game.viewed_ply(), 1);
+
+        // The rest of the game is kept as a variation of the new move.
+        play(&mut game, &[(51, 35)]);
+
+        assert_eq!(sans(&game), vec!["e4", "d5"]);
+        assert_eq!(game.variations().len(), 1);
+        assert_eq!(game.variations()[0].ply, 1);
+
+        _ = game.update(Message::Navigate(Navigation::Ply(1)));
+        _ = game.update(Message::PlayFromHere);
+        _ = game.update(Message::Redo);
+
+        assert_eq!(sans(&game), vec!["e4", "d5"]);
+        assert_eq!(game.viewed_ply(), 2);
+    }
+
+    #[test]
+    fn test_given_up_lines_are_variations() {
+        let mut game = started_game();
+
+        play(&mut game, &[(E2, E4), (52, 36), (G1, F3), (57, 42)]);
+
+        _ = game.update(Message::Navigate(Navigation::Ply(2)));
+        _ = game.update(Message::PlayFromHere);
+        play(&mut game, &[(F2, 29)]);
+
+        _ = game.update(Message::Navigate(Navigation::Ply(1)));
+        _ = game.update(Message::PlayFromHere);
+        play(&mut game, &[(51, 35)]);
+
+        let pgn = game.pgn().expect("A game was started").to_string();
+
+        assert_eq!(sans(&game), vec!["e4", "d5"]);
+        assert!(pgn.contains("1. e4 d5 (1... e5 2. f4 (2. Nf3 Nc6))"));
+    }
+
+    #[test]
+    fn test_replaying_an_undone_move_is_a_redo() {
+        let mut game = started_game();
+
+        play(&mut game, &[(E2, E4), (52, 36)]);
+        _ = game.update(Message::Undo);
+        play(&mut game, &[(52, 36)]);
+
+        assert_eq!(sans(&game), vec!["e4", "e5"]);
+        assert!(game.variations().is_empty());
+    }
+
+    #[test]
+    fn test_clocks_switch_and_flag() {
+        let mut game = ChessGame::default();
+
+        _ = game.update(Message::SelectTimeControl(Some(TimeControl::fischer(1, 0))));
+        _ = game.update(Message::Start);
+
+        assert_eq!(
+            game.clock().and_then(|clock| clock.running()),
+            Some(Players::White)
+        );
+
+        play(&mut game, &[(E2, E4)]);
+
+        assert_eq!(
+            game.clock().and_then(|clock| clock.running()),
+            Some(Players::Black)
+        );
+
+        // Black runs out of time while white has a whole army left.
+        _ = game.update(Message::Tick(Instant::now() + Duration::from_secs(61)));
+
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome::Timeout {
+                winner: Players::White
+            })
+        );
+        assert_eq!(game.clock().and_then(|clock| clock.running()), None);
+    }
+
+    #[test]
+    fn test_take_backs_restore_the_clocks() {
+        let mut game = ChessGame::default();
+
+        _ = game.update(Message::SelectTimeControl(Some(TimeControl::fischer(
+            1, 30,
+        ))));
+        _ = game.update(Message::Start);
+
+        play(&mut game, &[(E2, E4)]);
+
+        let white_time = |game: &ChessGame| {
+            game.clock()
+                .map(|clock| clock.remaining(Players::White, Instant::now()))
+                .unwrap_or_default()
+        };
+
+        // The increment is taken back with the move.
+        assert!(white_time(&game) > Duration::from_secs(80));
+
+        _ = game.update(Message::Undo);
+
+        assert!(white_time(&game) <= Duration::from_secs(60));
+        assert_eq!(
+            game.clock().and_then(|clock| clock.running()),
+            Some(Players::White)
+        );
+
+        _ = game.update(Message::Redo);
+
+        assert!(white_time(&game) > Duration::from_secs(80));
+        assert_eq!(
+            game.clock().and_then(|clock| clock.running()),
+            Some(Players::Black)
+        );
+    }
+
+    #[test]
+    fn test_custom_time_control() {
+        let mut game = ChessGame::default();
+
+        _ = game.update(Message::CustomMinutesChanged(String::from("2")));
+        _ = game.update(Message::CustomIncrementChanged(String::from("1")));
+        _ = game.update(Message::Start);
+
+        assert_eq!(
+            game.clock().map(|clock| clock.time_control()),
+            Some(TimeControl::fischer(2, 1))
+        );
+
+        // Text that is not a time leaves the last valid one selected.
+        _ = game.update(Message::CustomMinutesChanged(String::from("two")));
+        _ = game.update(Message::Start);
+
+        assert_eq!(
+            game.clock().map(|clock| clock.time_control()),
+            Some(TimeControl::fischer(2, 1))
+        );
+
+        _ = game.update(Message::SelectTimeControl(None));
+        _ = game.update(Message::Start);
+
+        assert!(game.clock().is_none());
+    }
+
+    #[test]
+    fn test_resignation_survives_take_backs() {
+        let mut game = started_game();
+
+        play(&mut game, &[(E2, E4), (52, 36)]);
+        _ = game.update(Message::Undo);
+        _ = game.update(Message::Resign);
+
+        for message in [Message::Undo, Message::Redo, Message::PlayFromHere] {
+            _ = game.update(message);
+        }
+
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome::Resignation {
+                winner: Players::White
+            })
+        );
+        assert_eq!(sans(&game), vec!["e4"]);
+    }
+
+    #[test]
+    fn test_resignation_ends_the_game() {
+        let mut game = started_game();
+
+        play(&mut game, &[(E2, E4), (52, 36)]);
+
+        // Between two players the side to move resigns.
+        _ = game.update(Message::Resign);
+
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome::Resignation {
+                winner: Players::Black
+            })
+        );
+        assert_eq!(game.movable_squares(), 0);
+
+        click(&mut game, G1);
+        click(&mut game, F3);
+
+        assert_eq!(sans(&game), vec!["e4", "e5"]);
+
+        let pgn = game.pgn().expect("A game is running");
+
+        assert_eq!(pgn.result, PgnResult::BlackWins);
+        assert_eq!(pgn.tag("Termination"), Some("normal"));
+        assert_eq!(pgn.moves.len(), 2);
+    }
+
+    #[test]
+    fn test_agreed_draw_and_start_screen() {
+        let mut game = started_game();
+
+        _ = game.update(Message::AgreeDraw);
+
+        assert_eq!(game.outcome(), Some(GameOutcome::DrawAgreed));
+
+        _ = game.update(Message::ShowStartScreen);
+
+        assert_eq!(game.outcome(), None);
+        assert!(game.pgn().is_none());
+    }
+
+    #[test]
+    fn test_rematch_swaps_colours() {
+        let mut game = ChessGame::default();
+
+        _ = game.update(Message::SelectOpponent(Some(Strength::Depth(1))));
+        _ = game.update(Message::Start);
+
+        play(&mut game, &[(E2, E4)]);
+
+        _ = game.update(Message::Resign);
+
+        let pgn = game.pgn().expect("A game is running");
+
+        assert_eq!(pgn.result, PgnResult::BlackWins);
+        assert_eq!(pgn.tag("White"), Some("Player"));
+        assert_eq!(pgn.tag("Black"), Some("Computer (Depth 1)"));
+
+        _ = game.update(Message::Rematch);
+
+        let pgn = game.pgn().expect("The rematch is running");
+
+        assert_eq!(game.outcome(), None);
+        assert!(game.moves().is_empty());
+        assert_eq!(pgn.tag("White"), Some("Computer (Depth 1)"));
+        assert_eq!(pgn.tag("Black"), Some("Player"));
+    }
+
+    #[test]
+    fn test_no_navigation_during_promotion() {
+        let mut game = started_game();
+
+        // 1. a4 b5 2. axb5 a6 3. bxa6 Bb7 4. axb7 Nc6, then bxa8 promotes.
+        play(
+            &mut game,
+            &[
+                (8, 24),
+                (49, 33),
+                (24, 33),
+                (48, 40),
+                (33, 40),
+                (58, 49),
+                (40, 49),
+                (57, 42),
+                (49, 56),
+            ],
+        );
+
+        assert_eq!(game.viewed_ply(), 8);
+
+        _ = game.update(Message::Navigate(Navigation::Ply(2)));
+
+        assert_eq!(game.viewed_ply(), 8);
+
+        _ = game.update(Message::PromotionTriggered(WQUEEN));
+
+        assert_eq!(sans(&game).last(), Some(&"bxa8=Q"));
+    }
+
+    #[test]
+    fn test_stale_promotion_choice_is_ignored() {
+        let mut game = ChessGame::default();
+
+        _ = game.update(Message::PromotionTriggered(WQUEEN));
+
+        let mut game = started_game();
+
+        _ = game.update(Message::PromotionTriggered(WQUEEN));
+
+        assert!(game.moves().is_empty());
+    }
+}
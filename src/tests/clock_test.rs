@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chessboard::Players,
+        clock::{Clock, IncrementKind, TimeControl, format_time},
+    };
+    use std::time::{Duration, Instant};
+
+    fn seconds(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_fischer_increment() {
+        let start = Instant::now();
+        let mut clock = Clock::new(TimeControl::fischer(5, 3));
+
+        clock.start(Players::White, start);
+
+        assert_eq!(
+            clock.remaining(Players::White, start + seconds(10)),
+            seconds(290)
+        );
+        assert_eq!(
+            clock.remaining(Players::Black, start + seconds(10)),
+            seconds(300)
+        );
+
+        clock.switch(start + seconds(10));
+
+        assert_eq!(clock.running(), Some(Players::Black));
+        assert_eq!(
+            clock.remaining(Players::White, start + seconds(20)),
+            seconds(293)
+        );
+        assert_eq!(
+            clock.remaining(Players::Black, start + seconds(20)),
+            seconds(290)
+        );
+    }
+
+    #[test]
+    fn test_bronstein_delay() {
+        let start = Instant::now();
+        let mut clock = Clock::new(TimeControl {
+            base: seconds(60),
+            increment: seconds(5),
+            kind: IncrementKind::Bronstein,
+        });
+
+        // A quick move costs nothing, a slow one all but the delay.
+        clock.start(Players::White, start);
+        clock.switch(start + seconds(2));
+        clock.switch(start + seconds(12));
+
+        assert_eq!(
+            clock.remaining(Players::White, start + seconds(12)),
+            seconds(60)
+        );
+        assert_eq!(
+            clock.remaining(Players::Black, start + seconds(12)),
+            seconds(55)
+        );
+    }
+
+    #[test]
+    fn test_running_out_of_time() {
+        let start = Instant::now();
+        let mut clock = Clock::new(TimeControl::fischer(1, 0));
+
+        assert_eq!(clock.flagged(start + seconds(120)), None);
+
+        clock.start(Players::White, start);
+
+        assert_eq!(clock.flagged(start + seconds(59)), None);
+        assert_eq!(clock.flagged(start + seconds(60)), Some(Players::White));
+
+        // A stopped clock keeps its time.
+        clock.stop(start + seconds(30));
+
+        assert_eq!(clock.running(), None);
+        assert_eq!(clock.flagged(start + seconds(120)), None);
+        assert_eq!(
+            clock.remaining(Players::White, start + seconds(120)),
+            seconds(30)
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(seconds(300)), "5:00");
+        assert_eq!(format_time(seconds(59)), "0:59");
+        assert_eq!(format_time(Duration::from_millis(9_470)), "0:09.4");
+        assert_eq!(format_time(seconds(3_725)), "1:02:05");
+
+        assert_eq!(TimeControl::fischer(15, 10).to_string(), "15+10");
+        assert_eq!(
+            TimeControl {
+                base: seconds(30),
+                increment: seconds(2),
+                kind: IncrementKind::Bronstein,
+            }
+            .to_string(),
+            "0.5 d2"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chessboard::{ChessBoard, Players},
+        evaluation::{MAX_PHASE, evaluate, game_phase},
+        tests::board_from,
+    };
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const WHITE_EXTRA_KNIGHT: &str = "rnbqkb1r/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const PASSED_PAWN: &str = "4k3/8/8/3P4/8/8/8/4K3 w - - 0 1";
+    const BLOCKED_PAWN: &str = "4k3/3p4/8/3P4/8/8/8/4K3 w - - 0 1";
+    const DOUBLED_PAWNS: &str = "4k3/pp6/8/8/8/8/P7/P3K3 w - - 0 1";
+    const HEALTHY_PAWNS: &str = "4k3/pp6/8/8/8/8/PP6/4K3 w - - 0 1";
+
+    /* Flip the board vertically and swap the colours, the side to move should get the same score. */
+    fn mirror(chessboard: &ChessBoard) -> ChessBoard {
+        let mut mirrored = chessboard.clone();
+
+        for position in 0..64 {
+            mirrored.set_square(position ^ 56, -chessboard.board[position]);
+        }
+
+        mirrored.white_king_position = chessboard.black_king_position ^ 56;
+        mirrored.black_king_position = chessboard.white_king_position ^ 56;
+        mirrored.side_to_move = match chessboard.side_to_move {
+            Players::White => Players::Black,
+            Players::Black => Players::White,
+        };
+        mirrored.castling_ability = [
+            chessboard.castling_ability[2],
+            chessboard.castling_ability[3],
+            chessboard.castling_ability[0],
+            chessboard.castling_ability[1],
+        ];
+        mirrored.en_passant_target_square = chessboard
+            .en_passant_target_square
+            .map(|square| square ^ 56);
+
+        mirrored
+    }
+
+    #[test]
+    fn test_start_position_is_equal() {
+        assert_eq!(evaluate(&ChessBoard::default()), 0);
+    }
+
+    #[test]
+    fn test_evaluation_is_symmetric() {
+        for fen in [KIWIPETE, WHITE_EXTRA_KNIGHT, PASSED_PAWN, DOUBLED_PAWNS] {
+            let board = board_from(fen);
+
+            assert_eq!(evaluate(&board), evaluate(&mirror(&board)), "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_score_is_from_side_to_move() {
+        let mut board = board_from(WHITE_EXTRA_KNIGHT);
+        let white_score = evaluate(&board);
+
+        board.side_to_move = Players::Black;
+
+        assert!(white_score > 200);
+        assert_eq!(evaluate(&board), -white_score);
+    }
+
+    #[test]
+    fn test_passed_pawn_bonus() {
+        assert!(evaluate(&board_from(PASSED_PAWN)) > evaluate(&board_from(BLOCKED_PAWN)) + 100);
+    }
+
+    #[test]
+    fn test_doubled_pawn_penalty() {
+        assert!(evaluate(&board_from(DOUBLED_PAWNS)) < evaluate(&board_from(HEALTHY_PAWNS)));
+    }
+
+    #[test]
+    fn test_game_phase() {
+        assert_eq!(game_phase(&ChessBoard::default().board), MAX_PHASE);
+        assert_eq!(game_phase(&board_from(PASSED_PAWN).board), 0);
+    }
+}
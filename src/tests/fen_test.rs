@@ -2,8 +2,8 @@
 mod tests {
     use crate::{
         chessboard::{
-            BBISHOP, BKING, BKNIGHT, BPAWN, BQUEEN, BROOK, EMPTY, WBISHOP, WKING, WKNIGHT, WPAWN,
-            WQUEEN, WROOK,
+            BBISHOP, BKING, BKNIGHT, BPAWN, BQUEEN, BROOK, ChessBoard, EMPTY, WBISHOP, WKING,
+            WKNIGHT, WPAWN, WQUEEN, WROOK,
         },
         fen::{
             FEN_STARTING_POSITION, Fen, FenArguments, FenError, FenErrorKind, FenType,
@@ -23,6 +23,10 @@ mod tests {
         fn set_fen_position(_fen: &str) -> Result<crate::chessboard::ChessBoard, FenError> {
             todo!()
         }
+
+        fn to_fen(&self) -> String {
+            todo!()
+        }
     }
 
     #[test]
@@ -109,6 +113,38 @@ mod tests {
         )
     }
 
+    #[test]
+    fn fen_writer_round_trip() {
+        for fen in [FEN_STARTING_POSITION, LEGAL_POS1, LEGAL_POS2] {
+            let board = ChessBoard::set_fen_position(fen).expect("Is valid FEN");
+
+            assert_eq!(board.to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn fen_writer_adds_default_counters() {
+        let board = ChessBoard::set_fen_position(KIWI_PETE).expect("Is valid FEN");
+
+        assert_eq!(board.to_fen(), format!("{KIWI_PETE} 0 1"));
+        assert_eq!(ChessBoard::set_fen_position(&board.to_fen()), Ok(board));
+    }
+
+    #[test]
+    fn fen_writer_after_moves() {
+        let mut board = ChessBoard::default();
+
+        for uci_move in ["e2e4", "c7c5", "g1f3"] {
+            let legal_move = board.parse_uci_move(uci_move).expect("Is legal");
+            board.make_move(legal_move);
+        }
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+    }
+
     #[test]
     fn fen_position_iterator() {
         let pos_iter = PositionIterator {
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::{
+        bitboard::Bitboards,
         chessboard::{BKING, BPAWN, Players, WKING, WPAWN},
         engine::king_is_attacked_by_pawns,
     };
@@ -13,9 +14,10 @@ mod tests {
 
         board[9] = BPAWN;
 
-        let is_checked = king_is_attacked_by_pawns(&board, 0, Players::White);
+        let is_checked =
+            king_is_attacked_by_pawns(&Bitboards::from_board(&board), 0, Players::White);
 
-        assert_eq!(is_checked, true);
+        assert!(is_checked);
     }
 
     #[test]
@@ -26,9 +28,10 @@ mod tests {
 
         board[8] = BPAWN;
 
-        let is_checked = king_is_attacked_by_pawns(&board, 1, Players::White);
+        let is_checked =
+            king_is_attacked_by_pawns(&Bitboards::from_board(&board), 1, Players::White);
 
-        assert_eq!(is_checked, true);
+        assert!(is_checked);
     }
 
     #[test]
@@ -39,9 +42,10 @@ mod tests {
 
         board[54] = BPAWN;
 
-        let is_checked = king_is_attacked_by_pawns(&board, 63, Players::Black);
+        let is_checked =
+            king_is_attacked_by_pawns(&Bitboards::from_board(&board), 63, Players::Black);
 
-        assert_eq!(is_checked, false);
+        assert!(!is_checked);
     }
 
     #[test]
@@ -52,8 +56,9 @@ mod tests {
 
         board[54] = WPAWN;
 
-        let is_checked = king_is_attacked_by_pawns(&board, 63, Players::Black);
+        let is_checked =
+            king_is_attacked_by_pawns(&Bitboards::from_board(&board), 63, Players::Black);
 
-        assert_eq!(is_checked, true);
+        assert!(is_checked);
     }
 }
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::{chessboard::ChessBoard, engine::ChessEngine, fen::Fen};
+
+    #[test]
+    fn test_en_passant_exposing_horizontal_pin() {
+        // Taking on c6 removes both pawns from the fifth rank and leaves the king to the rook on h5.
+        let chessboard =
+            ChessBoard::set_fen_position("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").expect("Is valid FEN");
+
+        assert!(chessboard.parse_uci_move("b5c6").is_err());
+        assert!(chessboard.parse_uci_move("b5b6").is_ok());
+    }
+
+    #[test]
+    fn test_en_passant_captures_checking_pawn() {
+        let chessboard = ChessBoard::set_fen_position("4k3/8/8/3pP3/4K3/8/8/8 w - d6 0 1")
+            .expect("Is valid FEN");
+
+        assert!(chessboard.is_checked());
+        assert!(chessboard.parse_uci_move("e5d6").is_ok());
+        // Pushing the pawn does nothing against the check.
+        assert!(chessboard.parse_uci_move("e5e6").is_err());
+    }
+
+    #[test]
+    fn test_double_check_only_allows_king_moves() {
+        // The rook on d1 could take the knight, but the rook on e8 would still give check.
+        let chessboard = ChessBoard::set_fen_position("4r2k/8/8/8/8/3n4/8/3RK3 w - - 0 1")
+            .expect("Is valid FEN");
+
+        let legal_moves = chessboard.legal_moves();
+
+        assert!(!legal_moves.is_empty());
+        assert!(legal_moves.iter().all(|legal_move| legal_move.from == 4));
+        assert!(chessboard.parse_uci_move("e1e2").is_err());
+    }
+
+    #[test]
+    fn test_pinned_piece_moves_along_pin() {
+        let chessboard = ChessBoard::set_fen_position("4k3/4r3/8/8/8/8/4R3/4K3 w - - 0 1")
+            .expect("Is valid FEN");
+
+        let mut rook_moves: Vec<String> = chessboard
+            .legal_moves()
+            .iter()
+            .filter(|legal_move| legal_move.from == 12)
+            .map(|legal_move| legal_move.to_string())
+            .collect();
+        rook_moves.sort();
+
+        assert_eq!(rook_moves, ["e2e3", "e2e4", "e2e5", "e2e6", "e2e7"]);
+    }
+
+    #[test]
+    fn test_king_cannot_step_back_along_checking_ray() {
+        let chessboard =
+            ChessBoard::set_fen_position("4k3/8/8/8/r3K3/8/8/8 w - - 0 1").expect("Is valid FEN");
+
+        assert!(chessboard.parse_uci_move("e4f4").is_err());
+        assert!(chessboard.parse_uci_move("e4e5").is_ok());
+    }
+
+    #[test]
+    fn test_castling_through_attacked_square() {
+        // The bishop on a6 covers f1, the king may still castle queen side.
+        let chessboard = ChessBoard::set_fen_position("4k3/8/b7/8/8/8/8/R3K2R w KQ - 0 1")
+            .expect("Is valid FEN");
+
+        assert!(chessboard.parse_uci_move("e1g1").is_err());
+        assert!(chessboard.parse_uci_move("e1c1").is_ok());
+    }
+}
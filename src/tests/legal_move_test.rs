@@ -3,6 +3,7 @@ mod tests {
     use crate::chessboard::{ChessBoard, EMPTY, Players, WPAWN};
     use crate::fen::Fen;
     use crate::moves::{LegalMove, MoveType};
+    use crate::zobrist::zobrist_key;
 
     #[test]
     fn test_update_normal_move_1() {
@@ -20,10 +21,11 @@ mod tests {
         let mut expected_board = ChessBoard::set_fen_position("4k3/3p4/8/8/8/8/5P2/B3K3 w - - 0 1")
             .expect("normal_move_test");
 
-        expected_board.board[45] = expected_board.board[0];
-        expected_board.board[0] = EMPTY;
+        expected_board.set_square(45, expected_board.board[0]);
+        expected_board.set_square(0, EMPTY);
         expected_board.half_move_clock += 1;
         expected_board.side_to_move = Players::Black;
+        expected_board.zobrist_key = zobrist_key(&expected_board);
 
         assert_eq!(
             chessboard, expected_board,
@@ -49,9 +51,10 @@ mod tests {
         let mut expected_board = ChessBoard::set_fen_position("4k3/3p4/8/8/8/8/5P2/4K3 w - - 0 1")
             .expect("test should be valid!");
 
-        expected_board.board[13] = EMPTY;
-        expected_board.board[21] = WPAWN;
+        expected_board.set_square(13, EMPTY);
+        expected_board.set_square(21, WPAWN);
         expected_board.side_to_move = Players::Black;
+        expected_board.zobrist_key = zobrist_key(&expected_board);
 
         assert_eq!(
             chessboard, expected_board,
@@ -72,10 +75,11 @@ mod tests {
 
         let mut expected_board = ChessBoard::default();
 
-        expected_board.board[8] = EMPTY;
-        expected_board.board[24] = WPAWN;
+        expected_board.set_square(8, EMPTY);
+        expected_board.set_square(24, WPAWN);
         expected_board.side_to_move = Players::Black;
         expected_board.en_passant_target_square = Some(16);
+        expected_board.zobrist_key = zobrist_key(&expected_board);
 
         chessboard.make_move(legal_move);
 
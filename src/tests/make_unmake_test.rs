@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::Bitboards,
+        chessboard::{BPAWN, ChessBoard, EMPTY, WROOK},
+        engine::ChessEngine,
+        fen::Fen,
+        zobrist::zobrist_key,
+    };
+
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const EN_PASSANT: &str = "4k3/8/8/Pp6/8/8/8/4K3 w - b6 0 1";
+    const PROMOTIONS: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1R1K b kq - 1 1";
+    const CHECKS: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+
+    /*
+     * Walk the move tree and check that every move leaves the board exactly as it was after unmaking it.
+     */
+    fn assert_unmake_restores(chessboard: &mut ChessBoard, depth: u32) {
+        if depth == 0 {
+            return;
+        }
+
+        for legal_move in chessboard.legal_moves() {
+            let before = chessboard.clone();
+
+            let saved_state = chessboard.make_move(legal_move);
+            assert_eq!(
+                chessboard.bitboards,
+                Bitboards::from_board(&chessboard.board)
+            );
+            assert_unmake_restores(chessboard, depth - 1);
+            chessboard.unmake_move(legal_move, saved_state);
+
+            assert_eq!(*chessboard, before, "{legal_move} in {}", before.to_fen());
+        }
+    }
+
+    #[test]
+    fn test_unmake_restores_position() {
+        for fen in [KIWI_PETE, EN_PASSANT, PROMOTIONS, CHECKS] {
+            let mut chessboard = ChessBoard::set_fen_position(fen).expect("Is valid FEN");
+
+            assert_unmake_restores(&mut chessboard, 3);
+        }
+
+        assert_unmake_restores(&mut ChessBoard::default(), 3);
+    }
+
+    #[test]
+    fn test_saved_state_captured_piece() {
+        let mut chessboard = ChessBoard::set_fen_position(EN_PASSANT).expect("Is valid FEN");
+        let en_passant = chessboard.parse_uci_move("a5b6").expect("Is legal");
+
+        let saved_state = chessboard.make_move(en_passant);
+
+        assert_eq!(saved_state.captured_piece, BPAWN);
+        assert_eq!(saved_state.en_passant_target_square, Some(41));
+        assert_eq!(chessboard.board[33], EMPTY);
+
+        let mut chessboard = ChessBoard::set_fen_position(PROMOTIONS).expect("Is valid FEN");
+        let promotion = chessboard.parse_uci_move("b2a1q").expect("Is legal");
+
+        assert_eq!(chessboard.make_move(promotion).captured_piece, WROOK);
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut chessboard = ChessBoard::set_fen_position(EN_PASSANT).expect("Is valid FEN");
+        let before = chessboard.clone();
+
+        let saved_state = chessboard.make_null_move();
+
+        assert_eq!(chessboard.to_fen(), "4k3/8/8/Pp6/8/8/8/4K3 b - - 1 1");
+        assert_eq!(chessboard.zobrist_key, zobrist_key(&chessboard));
+
+        chessboard.unmake_null_move(saved_state);
+
+        assert_eq!(chessboard, before);
+    }
+}
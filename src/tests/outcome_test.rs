@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chessboard::{ChessBoard, Players},
+        engine::ChessEngine,
+        outcome::{GameOutcome, has_insufficient_material, has_mating_material, timeout_outcome},
+        pgn::PgnResult,
+        tests::board_from,
+    };
+
+    const FOOLS_MATE: &str = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+    const STALEMATE: &str = "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1";
+    const SEVENTY_FIVE_MOVES: &str = "4k3/8/8/8/8/8/4P3/R3K3 w - - 150 120";
+    const FIFTY_MOVES: &str = "4k3/8/8/8/8/8/4P3/R3K3 w - - 100 120";
+    const BISHOPS_SAME_COLOR: &str = "4k3/8/3b4/8/8/8/8/2B1K3 w - - 0 1";
+    const BISHOPS_OPPOSITE_COLOR: &str = "4k3/8/2b5/8/8/8/8/2B1K3 w - - 0 1";
+
+    #[test]
+    fn test_checkmate() {
+        let board = board_from(FOOLS_MATE);
+
+        assert_eq!(
+            board.outcome(&[]),
+            Some(GameOutcome::Checkmate {
+                winner: Players::Black
+            })
+        );
+    }
+
+    #[test]
+    fn test_stalemate() {
+        let board = board_from(STALEMATE);
+
+        assert_eq!(board.outcome(&[]), Some(GameOutcome::Stalemate));
+    }
+
+    #[test]
+    fn test_start_position_has_no_outcome() {
+        assert_eq!(ChessBoard::default().outcome(&[]), None);
+    }
+
+    #[test]
+    fn test_move_rules() {
+        assert_eq!(
+            board_from(FIFTY_MOVES).outcome(&[]),
+            Some(GameOutcome::FiftyMoveRule)
+        );
+        assert_eq!(
+            board_from(SEVENTY_FIVE_MOVES).outcome(&[]),
+            Some(GameOutcome::SeventyFiveMoveRule)
+        );
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        assert!(has_insufficient_material(
+            &board_from("4k3/8/8/8/8/8/8/4K3 w - - 0 1").board
+        ));
+        assert!(has_insufficient_material(
+            &board_from("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1").board
+        ));
+        assert!(has_insufficient_material(
+            &board_from(BISHOPS_SAME_COLOR).board
+        ));
+        assert!(!has_insufficient_material(
+            &board_from(BISHOPS_OPPOSITE_COLOR).board
+        ));
+        assert!(!has_insufficient_material(
+            &board_from("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1").board
+        ));
+        assert!(!has_insufficient_material(&ChessBoard::default().board));
+    }
+
+    #[test]
+    fn test_mating_material() {
+        let board = board_from("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1").board;
+
+        assert!(has_mating_material(&board, Players::White));
+        assert!(!has_mating_material(&board, Players::Black));
+
+        assert!(!has_mating_material(
+            &board_from(BISHOPS_SAME_COLOR).board,
+            Players::White
+        ));
+        assert!(has_mating_material(
+            &board_from("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").board,
+            Players::White
+        ));
+        assert!(has_mating_material(
+            &board_from("4k3/p7/8/8/8/8/8/4K3 w - - 0 1").board,
+            Players::Black
+        ));
+
+        // Bishops on one square color need a blocker that can stand on the other color.
+        assert!(has_mating_material(
+            &board_from("4k1n1/8/8/8/8/8/8/4KB2 w - - 0 1").board,
+            Players::White
+        ));
+        assert!(!has_mating_material(
+            &board_from("2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1").board,
+            Players::White
+        ));
+        assert!(!has_mating_material(
+            &board_from("4k3/q7/8/8/8/8/8/1N2K3 w - - 0 1").board,
+            Players::White
+        ));
+    }
+
+    #[test]
+    fn test_timeout() {
+        // White runs out of time, black can still win with its pawn.
+        let board = board_from("4k3/p7/8/8/8/8/8/R3K3 w - - 0 1").board;
+
+        assert_eq!(
+            timeout_outcome(&board, Players::White),
+            GameOutcome::Timeout {
+                winner: Players::Black
+            }
+        );
+
+        // A lone knight can not checkmate a bare king.
+        let board = board_from("4k3/8/8/8/8/8/8/QN2K3 w - - 0 1").board;
+
+        assert_eq!(
+            timeout_outcome(&board, Players::White),
+            GameOutcome::TimeoutVsInsufficientMaterial
+        );
+        assert_eq!(
+            timeout_outcome(&board, Players::Black),
+            GameOutcome::Timeout {
+                winner: Players::White
+            }
+        );
+
+        // It can when the king has a pawn that may block it in.
+        let board = board_from("4k3/p7/8/8/8/8/8/1N2K3 w - - 0 1").board;
+
+        assert_eq!(
+            timeout_outcome(&board, Players::Black),
+            GameOutcome::Timeout {
+                winner: Players::White
+            }
+        );
+    }
+
+    #[test]
+    fn test_ended_by_the_players() {
+        let resignation = GameOutcome::Resignation {
+            winner: Players::White,
+        };
+
+        assert_eq!(resignation.winner(), Some(Players::White));
+        assert_eq!(resignation.reason(), "Resignation");
+        assert_eq!(PgnResult::from(resignation), PgnResult::WhiteWins);
+
+        assert!(GameOutcome::DrawAgreed.is_draw());
+        assert!(!GameOutcome::DrawAgreed.is_claimable());
+        assert_eq!(PgnResult::from(GameOutcome::DrawAgreed), PgnResult::Draw);
+        assert_eq!(
+            PgnResult::from(GameOutcome::TimeoutVsInsufficientMaterial),
+            PgnResult::Draw
+        );
+    }
+
+    #[test]
+    fn test_repetitions() {
+        let mut board = ChessBoard::default();
+        let mut previous_positions = Vec::new();
+
+        // Shuffle the knights back and forth: g1f3 g8f6 f3g1 f6g8
+        let knight_moves = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        for cycle in 0..4 {
+            for knight_move in knight_moves {
+                let legal_move = board
+                    .legal_moves()
+                    .into_iter()
+                    .find(|legal_move| legal_move.to_string() == knight_move)
+                    .expect("Knight move should be legal");
+
+                previous_positions.push(board.clone());
+                board.make_move(legal_move);
+            }
+
+            let expected = match cycle {
+                0 => None,
+                1 => Some(GameOutcome::ThreefoldRepetition),
+                2 => Some(GameOutcome::ThreefoldRepetition),
+                _ => Some(GameOutcome::FivefoldRepetition),
+            };
+
+            assert_eq!(board.outcome(&previous_positions), expected);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chessboard::ChessBoard,
+        engine::ChessEngine,
+        fen::{FEN_STARTING_POSITION, Fen, FenArguments, FenErrorKind},
+        perft::{
+            PerftCommand, PerftCommandError, PerftCommandErrorKind, PerftSuiteCommand,
+            PerftSuiteEntry, parse_perft_suite, run_perft_suite,
+        },
+        transposition::PerftTable,
+    };
+
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(
+            PerftCommand::parse(&["--depth", "6"]),
+            Ok(PerftCommand {
+                fen: String::from(FEN_STARTING_POSITION),
+                depth: 6,
+                divide: false,
+                threads: 1,
+                hash_mb: None,
+            })
+        );
+
+        assert_eq!(
+            PerftCommand::parse(&[
+                "--fen",
+                KIWI_PETE,
+                "--divide",
+                "--threads",
+                "8",
+                "--depth",
+                "4",
+                "--hash",
+                "32",
+            ]),
+            Ok(PerftCommand {
+                fen: String::from(KIWI_PETE),
+                depth: 4,
+                divide: true,
+                threads: 8,
+                hash_mb: Some(32),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let kind = |arguments: &[&str]| PerftCommand::parse(arguments).map_err(|e| e.kind);
+
+        assert_eq!(kind(&[]), Err(PerftCommandErrorKind::MissingDepth));
+        assert_eq!(kind(&["--depth"]), Err(PerftCommandErrorKind::MissingValue));
+        assert_eq!(
+            kind(&["--depth", "six"]),
+            Err(PerftCommandErrorKind::InvalidValue)
+        );
+        assert_eq!(
+            kind(&["--depth", "3", "--threads", "0"]),
+            Err(PerftCommandErrorKind::InvalidValue)
+        );
+        assert_eq!(
+            kind(&["--depth", "3", "--nodes"]),
+            Err(PerftCommandErrorKind::UnknownArgument)
+        );
+
+        let command = PerftCommand::parse(&["--fen", "8/8/8 w - -", "--depth", "1"])
+            .expect("Arguments are valid");
+
+        assert!(matches!(
+            command.run().map_err(|e| e.kind),
+            Err(PerftCommandErrorKind::InvalidFen(_))
+        ));
+
+        // A position without kings is rejected before any move is generated.
+        let command = PerftCommand::parse(&["--fen", "8/8/8/8/8/8/8/8 w - - 0 1", "--depth", "1"])
+            .expect("Arguments are valid");
+
+        assert_eq!(
+            command.run().map_err(|e| e.kind),
+            Err(PerftCommandErrorKind::InvalidFen(
+                FenErrorKind::InvalidArgument(FenArguments::Position)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_divide_output() {
+        let command = PerftCommand::parse(&["--depth", "2", "--divide", "--threads", "3"])
+            .expect("Arguments are valid");
+
+        let lines = command.run().expect("The start position is valid");
+
+        assert_eq!(lines.len(), 20 + 4);
+        assert_eq!(lines[0], "a2a3: 20");
+        assert_eq!(lines[19], "h2h4: 20");
+        assert_eq!(lines[20], "");
+        assert_eq!(lines[21], "Nodes searched: 400");
+        assert!(lines[23].starts_with("Nodes per second: "));
+    }
+
+    #[test]
+    fn test_parallel_perft_matches_perft() {
+        let board = ChessBoard::set_fen_position(KIWI_PETE).expect("Is valid FEN");
+        let table = PerftTable::new(1);
+        let expected = board.perft(3);
+
+        for threads in [1, 2, 7, 100] {
+            assert_eq!(board.parallel_perft(3, threads, None), expected);
+            assert_eq!(board.parallel_perft(3, threads, Some(&table)), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_epd_lines() {
+        assert_eq!(
+            PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 15 ;D2 66"),
+            Ok(Some(PerftSuiteEntry {
+                fen: String::from("4k3/8/8/8/8/8/8/4K2R w K - 0 1"),
+                expected: vec![(1, 15), (2, 66)],
+            }))
+        );
+
+        // Impossible positions are rejected with the line they are on, before any perft runs.
+        for (line, kind) in [
+            (
+                "8/8/8/8/8/8/8/8 w - - 0 1 ;D1 0",
+                FenErrorKind::InvalidArgument(FenArguments::Position),
+            ),
+            (
+                "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1 ;D1 20",
+                FenErrorKind::SideNotToMoveInCheck,
+            ),
+        ] {
+            assert_eq!(
+                PerftSuiteEntry::parse(line),
+                Err(PerftCommandError::new(
+                    PerftCommandErrorKind::InvalidFen(kind),
+                    String::from(line)
+                ))
+            );
+        }
+
+        assert_eq!(PerftSuiteEntry::parse("   "), Ok(None));
+        assert_eq!(PerftSuiteEntry::parse("# comment ;D1 3"), Ok(None));
+
+        for line in [
+            "4k3/8/8/8/8/8/8/4K2R w K - 0 1",
+            "4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1",
+            "4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;X1 15",
+            "4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 many",
+            " ;D1 15",
+        ] {
+            assert_eq!(
+                PerftSuiteEntry::parse(line).map_err(|e| e.kind),
+                Err(PerftCommandErrorKind::InvalidEpd),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_suite_reports_mismatches() {
+        let entries = parse_perft_suite(
+            "# Kiwipete with a wrong count at depth 2\n\
+             r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ;D1 48 ;D2 2040 ;D3 97862\n\
+             \n\
+             8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1 ;D1 14 ;D2 191 ;D3 2812\n",
+        )
+        .expect("The suite is valid");
+
+        assert_eq!(entries.len(), 2);
+
+        let mut reported = Vec::new();
+
+        let mismatches = run_perft_suite(&entries, None, 2, |entry, mismatch| {
+            reported.push((entry.fen.clone(), mismatch.is_some()));
+        })
+        .expect("The positions are valid");
+
+        assert_eq!(reported.iter().filter(|(_, failed)| *failed).count(), 1);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].depth, 2);
+        assert_eq!(mismatches[0].expected, 2040);
+        assert_eq!(mismatches[0].found, 2039);
+        assert_eq!(mismatches[0].divide.len(), 48);
+
+        // Depths above the maximum are not checked.
+        assert!(
+            run_perft_suite(&entries, Some(1), 1, |_, _| {})
+                .expect("The positions are valid")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_suite_command() {
+        assert_eq!(
+            PerftSuiteCommand::parse(&["perftsuite.epd", "--depth", "5", "--threads", "4"]),
+            Ok(PerftSuiteCommand {
+                path: String::from("perftsuite.epd"),
+                max_depth: Some(5),
+                threads: 4,
+            })
+        );
+
+        assert_eq!(
+            PerftSuiteCommand::parse(&["--depth", "5"]).map_err(|e| e.kind),
+            Err(PerftCommandErrorKind::MissingValue)
+        );
+        assert_eq!(
+            PerftSuiteCommand::parse(&["a.epd", "b.epd"]).map_err(|e| e.kind),
+            Err(PerftCommandErrorKind::UnknownArgument)
+        );
+    }
+}
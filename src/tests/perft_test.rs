@@ -4,7 +4,12 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::{chessboard::ChessBoard, engine::ChessEngine, fen::Fen};
+    use crate::{
+        chessboard::ChessBoard,
+        engine::ChessEngine,
+        fen::Fen,
+        perft::{parse_perft_suite, run_perft_suite},
+    };
 
     const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
     const EN_PASSANT_1: &str = "4k3/8/8/Pp6/8/8/8/4K3 w - b6 0 1";
@@ -14,6 +19,28 @@ mod tests {
     const PERFT_POS_4: &str =
         "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
 
+    #[test]
+    fn fen_round_trip_test() {
+        for fen in [
+            EN_PASSANT_1,
+            PERFT_POS_1,
+            PERFT_POS_2,
+            PERFT_POS_3,
+            PERFT_POS_4,
+        ] {
+            let board = ChessBoard::set_fen_position(fen).expect("Is valid FEN");
+
+            assert_eq!(board.to_fen(), fen);
+        }
+
+        let kiwi_pete = ChessBoard::set_fen_position(KIWI_PETE).expect("Is valid FEN");
+
+        assert_eq!(
+            ChessBoard::set_fen_position(&kiwi_pete.to_fen()),
+            Ok(kiwi_pete)
+        );
+    }
+
     #[test]
     fn start_pos_test() {
         let board = ChessBoard::default();
@@ -76,4 +103,36 @@ mod tests {
 
         assert_eq!(leaf_count, 3894594);
     }
+
+    /*
+     * Runs perft/perftsuite.epd, or the file in PERFT_SUITE, up to the depth in PERFT_DEPTH if set.
+     * Opt in with cargo test --release perft_suite -- --ignored
+     */
+    #[test]
+    #[ignore]
+    fn perft_suite() {
+        let path = std::env::var("PERFT_SUITE")
+            .unwrap_or_else(|_| format!("{}/perft/perftsuite.epd", env!("CARGO_MANIFEST_DIR")));
+        let max_depth = std::env::var("PERFT_DEPTH")
+            .ok()
+            .map(|depth| depth.parse().expect("PERFT_DEPTH should be a number"));
+
+        let contents = std::fs::read_to_string(&path).expect("The suite file should be readable");
+        let entries = parse_perft_suite(&contents).expect("The suite file should be valid");
+
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+
+        let mismatches = run_perft_suite(&entries, max_depth, threads, |_, mismatch| {
+            if let Some(mismatch) = mismatch {
+                eprintln!("{mismatch}");
+            }
+        })
+        .expect("The suite positions should be valid");
+
+        assert!(
+            mismatches.is_empty(),
+            "{} positions failed",
+            mismatches.len()
+        );
+    }
 }
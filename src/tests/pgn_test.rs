@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chessboard::ChessBoard,
+        fen::{Fen, FenArguments, FenErrorKind},
+        moves::LegalMoveParseErrorKind,
+        pgn::{
+            PgnError, PgnErrorKind, PgnGame, PgnReader, PgnResult,
+            reading::parse_pgn,
+            writing::{MAX_LINE_LENGTH, write_pgn},
+        },
+    };
+
+    const OPERA_GAME: &str = r#"[Event "Paris"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[Round "?"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+[Annotator "Someone \"quoted\""]
+
+{A famous game} 1. e4 e5 2. Nf3 d6 3. d4 Bg4 $6 {Weak} 4. dxe5 Bxf3 (4... dxe5 5.
+Qxd8+ Kxd8 6. Nxe5 (6. Bc4 {also good}) 6... Be6) 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3
+Qe7 8. Nc3 c6 9. Bg5 b5?! 10. Nxb5! cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7
+Rxd7 14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0
+"#;
+
+    const OPERA_FINAL_FEN: &str = "1n1Rkb1r/p4ppp/4q3/4p1B1/4P3/8/PPP2PPP/2K5 b k - 1 17";
+
+    const SET_UP_GAME: &str = r#"[Event "Endgame"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 40"]
+
+40... Kd7 41. e4 ; rest of line comment
+Kc6 *
+"#;
+
+    #[test]
+    fn test_parse_tags_and_replay() {
+        let game = parse_pgn(OPERA_GAME).expect("Is valid PGN");
+
+        assert_eq!(game.tag("White"), Some("Paul Morphy"));
+        assert_eq!(game.tag("Annotator"), Some("Someone \"quoted\""));
+        assert_eq!(game.result, PgnResult::WhiteWins);
+        assert_eq!(game.moves.len(), 33);
+        assert_eq!(game.final_position().to_fen(), OPERA_FINAL_FEN);
+    }
+
+    #[test]
+    fn test_parse_comments_nags_and_variations() {
+        let game = parse_pgn(OPERA_GAME).expect("Is valid PGN");
+
+        assert_eq!(game.initial_comments, vec!["A famous game"]);
+        assert_eq!(game.moves[5].nags, vec![6]);
+        assert_eq!(game.moves[5].comments, vec!["Weak"]);
+        assert_eq!(game.moves[17].nags, vec![6]);
+        assert_eq!(game.moves[18].nags, vec![1]);
+
+        let variation = &game.moves[7].variations[0];
+
+        assert_eq!(variation.moves[0].san, "dxe5");
+        assert_eq!(variation.moves[1].san, "Qxd8+");
+        assert_eq!(variation.moves[3].variations[0].moves[0].san, "Bc4");
+        assert_eq!(
+            variation.moves[3].variations[0].moves[0].comments,
+            vec!["also good"]
+        );
+    }
+
+    #[test]
+    fn test_parse_set_up() {
+        let game = parse_pgn(SET_UP_GAME).expect("Is valid PGN");
+
+        assert_eq!(game.moves.len(), 3);
+        assert_eq!(game.moves[1].comments, vec!["rest of line comment"]);
+        assert_eq!(game.result, PgnResult::Unknown);
+        assert_eq!(
+            game.final_position().to_fen(),
+            "8/8/2k5/8/4P3/8/8/4K3 w - - 1 42"
+        );
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        for pgn in [OPERA_GAME, SET_UP_GAME] {
+            let game = parse_pgn(pgn).expect("Is valid PGN");
+            let written = write_pgn(&game);
+
+            assert!(
+                written
+                    .lines()
+                    .all(|line| line.chars().count() <= MAX_LINE_LENGTH),
+                "{written}"
+            );
+            let reparsed = parse_pgn(&written).expect("Written PGN is valid");
+
+            assert_eq!(reparsed.initial_comments, game.initial_comments);
+            assert_eq!(reparsed.moves, game.moves);
+            assert_eq!(reparsed.result, game.result);
+            assert_eq!(write_pgn(&reparsed), written);
+        }
+    }
+
+    #[test]
+    fn test_write_new_game() {
+        let mut game = PgnGame::new(
+            ChessBoard::set_fen_position("4k3/8/8/8/8/8/4P3/4K3 b - - 0 40").expect("Valid"),
+        );
+
+        for uci_move in ["e8d7", "e2e4"] {
+            let legal_move = game
+                .final_position()
+                .parse_uci_move(uci_move)
+                .expect("Is legal");
+            game.push_move(legal_move);
+        }
+        game.set_result(PgnResult::Draw);
+
+        assert_eq!(
+            write_pgn(&game),
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n\
+             [Black \"?\"]\n[Result \"1/2-1/2\"]\n[SetUp \"1\"]\n\
+             [FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n40... Kd7 41. e4 1/2-1/2\n"
+        );
+    }
+
+    #[test]
+    fn test_stream_multiple_games() {
+        let file = format!("{OPERA_GAME}\n{SET_UP_GAME}\n% escaped line\n\n{OPERA_GAME}");
+
+        let games: Vec<PgnGame> = PgnReader::new(file.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("Are valid games");
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[1].tag("Event"), Some("Endgame"));
+        assert_eq!(games[0], games[2]);
+    }
+
+    #[test]
+    fn test_stream_results_in_comments() {
+        // Neither the result in the comment nor the bracket line inside it ends the first game.
+        let file = format!(
+            "1. e4 {{White resigned here, 0-1\n[or did they?]}} e5 ; 1-0\n2. Nf3 *\n{OPERA_GAME}"
+        );
+
+        let games: Vec<PgnGame> = PgnReader::new(file.as_bytes())
+            .collect::<Result<_, _>>()
+            .expect("Are valid games");
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].moves.len(), 3);
+        assert_eq!(games[0].result, PgnResult::Unknown);
+        assert_eq!(games[1].result, PgnResult::WhiteWins);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_pgn("1. e4 e5 2. Ke3 *"),
+            Err(PgnError::new(
+                PgnErrorKind::InvalidMove(LegalMoveParseErrorKind::Illegal),
+                String::from("Ke3")
+            ))
+        );
+        assert_eq!(
+            parse_pgn("1. e4 (1. d4 e5 *"),
+            Err(PgnError::new(
+                PgnErrorKind::UnbalancedVariation,
+                String::from("*")
+            ))
+        );
+        assert_eq!(
+            parse_pgn("1. e4 e5) *"),
+            Err(PgnError::new(
+                PgnErrorKind::UnbalancedVariation,
+                String::from(")")
+            ))
+        );
+        assert_eq!(
+            parse_pgn("[Event \"x]\n\n1. e4 *").map(|_| ()),
+            Err(PgnError::new(
+                PgnErrorKind::InvalidTag,
+                String::from("[Event \"x]")
+            ))
+        );
+        assert_eq!(
+            parse_pgn("[FEN \"8/8/8 w - - 0 1\"]\n\n*").map(|_| ()),
+            Err(PgnError::new(
+                PgnErrorKind::InvalidFen(FenErrorKind::InvalidArgument(FenArguments::Position)),
+                String::from("8/8/8 w - - 0 1")
+            ))
+        );
+        assert_eq!(
+            parse_pgn("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n*").map(|_| ()),
+            Err(PgnError::new(
+                PgnErrorKind::InvalidFen(FenErrorKind::InvalidArgument(FenArguments::Position)),
+                String::from("8/8/8/8/8/8/8/8 w - - 0 1")
+            ))
+        );
+        assert_eq!(
+            parse_pgn("[FEN \"4k3/4R3/8/8/8/8/8/4K3 w - - 0 1\"]\n\n*").map(|_| ()),
+            Err(PgnError::new(
+                PgnErrorKind::InvalidFen(FenErrorKind::SideNotToMoveInCheck),
+                String::from("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1")
+            ))
+        );
+    }
+}
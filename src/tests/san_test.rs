@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chessboard::ChessBoard,
+        engine::ChessEngine,
+        moves::{LegalMoveParseError, LegalMoveParseErrorKind},
+        tests::board_from,
+    };
+
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+    const PROMOTION: &str = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    const DISAMBIGUATION: &str = "4k3/8/8/8/1N3N2/8/1N6/4K3 w - - 0 1";
+    const FOOLS_MATE_SETUP: &str = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+
+    fn uci_to_san(board: &ChessBoard, uci_move: &str) -> String {
+        board.move_to_san(board.parse_uci_move(uci_move).expect("Is legal"))
+    }
+
+    #[test]
+    fn test_san_output() {
+        let board = board_from(KIWI_PETE);
+
+        assert_eq!(uci_to_san(&board, "e1c1"), "O-O-O");
+        assert_eq!(uci_to_san(&board, "e1g1"), "O-O");
+        assert_eq!(uci_to_san(&board, "d5e6"), "dxe6");
+        assert_eq!(uci_to_san(&board, "e5f7"), "Nxf7");
+        assert_eq!(uci_to_san(&board, "f3h3"), "Qxh3");
+        assert_eq!(uci_to_san(&board, "a2a4"), "a4");
+    }
+
+    #[test]
+    fn test_san_disambiguation() {
+        let board = board_from(DISAMBIGUATION);
+
+        assert_eq!(uci_to_san(&board, "b4d3"), "Nb4d3");
+        assert_eq!(uci_to_san(&board, "f4d3"), "Nfd3");
+        assert_eq!(uci_to_san(&board, "b2d3"), "N2d3");
+        assert_eq!(uci_to_san(&board, "b4a6"), "Na6");
+    }
+
+    #[test]
+    fn test_san_promotion_and_check() {
+        let board = board_from(PROMOTION);
+
+        assert_eq!(uci_to_san(&board, "a7b8q"), "axb8=Q+");
+        assert_eq!(uci_to_san(&board, "a7a8n"), "a8=N");
+    }
+
+    #[test]
+    fn test_san_checkmate() {
+        let board = board_from(FOOLS_MATE_SETUP);
+
+        assert_eq!(uci_to_san(&board, "d8h4"), "Qh4#");
+    }
+
+    #[test]
+    fn test_san_round_trip() {
+        for fen in [KIWI_PETE, PROMOTION, DISAMBIGUATION] {
+            let board = board_from(fen);
+
+            for legal_move in board.legal_moves() {
+                let san = board.move_to_san(legal_move);
+
+                assert_eq!(board.parse_san_move(&san), Ok(legal_move), "{san}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_san_input_variants() {
+        let board = board_from(KIWI_PETE);
+
+        assert_eq!(board.parse_san_move("0-0-0"), board.parse_uci_move("e1c1"));
+        assert_eq!(board.parse_san_move("O-O+"), board.parse_uci_move("e1g1"));
+        assert_eq!(board.parse_san_move("Nxf7!?"), board.parse_uci_move("e5f7"));
+
+        let board = board_from(PROMOTION);
+
+        assert_eq!(board.parse_san_move("axb8Q"), board.parse_uci_move("a7b8q"));
+        assert_eq!(board.parse_san_move("a8=N"), board.parse_uci_move("a7a8n"));
+    }
+
+    #[test]
+    fn test_san_input_errors() {
+        let error = |kind, given: &str| Err(LegalMoveParseError::new(kind, given.to_string()));
+
+        let board = board_from(DISAMBIGUATION);
+
+        assert_eq!(
+            board.parse_san_move("Nd3"),
+            error(LegalMoveParseErrorKind::Ambiguous, "Nd3")
+        );
+        assert_eq!(
+            board.parse_san_move("Nbd3"),
+            error(LegalMoveParseErrorKind::Ambiguous, "Nbd3")
+        );
+        assert_eq!(
+            board.parse_san_move("Qd3"),
+            error(LegalMoveParseErrorKind::Illegal, "Qd3")
+        );
+        assert_eq!(
+            board.parse_san_move("Nz3"),
+            error(LegalMoveParseErrorKind::Malformed, "Nz3")
+        );
+
+        let board = board_from(PROMOTION);
+
+        assert_eq!(
+            board.parse_san_move("axb8"),
+            error(LegalMoveParseErrorKind::MissingPromotion, "axb8")
+        );
+    }
+}
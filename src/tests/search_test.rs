@@ -0,0 +1,282 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        chessboard::ChessBoard,
+        engine::ChessEngine,
+        search::{
+            MATE_SCORE, MAX_DEPTH, SearchFeatures, SearchLimits, SearchResult, is_mate_score,
+            iterative_deepening,
+        },
+        tests::board_from,
+        transposition::TranspositionTable,
+    };
+
+    const MATE_IN_ONE: &str = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+    const MATE_IN_TWO: &str = "r1bq2r1/b4pk1/p1pp1p2/1p2pP2/1P2P1PB/3P4/1PPQ2P1/R3K2R w - - 0 1";
+    const MATED_IN_ONE: &str = "r5k1/8/8/8/8/5p1p/5P1P/6K1 w - - 0 1";
+    const HANGING_QUEEN: &str = "4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1";
+    const STALEMATE: &str = "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1";
+    const DEFENDED_KNIGHT: &str = "4k3/8/4p3/3n4/8/8/8/3QK3 w - - 0 1";
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    /* A small table, so every search starts without entries of earlier ones. */
+    fn table() -> TranspositionTable {
+        TranspositionTable::new(1)
+    }
+
+    fn depth_limit(depth: u32) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_finds_mate_in_one() {
+        let result = board_from(MATE_IN_ONE).search(&depth_limit(4), &table());
+
+        assert_eq!(
+            result.best_move.map(|m| m.to_string()).as_deref(),
+            Some("a1a8")
+        );
+        assert_eq!(result.mate_in(), Some(1));
+    }
+
+    #[test]
+    fn test_finds_mate_in_two() {
+        let result = board_from(MATE_IN_TWO).search(&depth_limit(4), &table());
+
+        assert_eq!(
+            result.best_move.map(|m| m.to_string()).as_deref(),
+            Some("d2h6")
+        );
+        assert_eq!(result.mate_in(), Some(2));
+        assert_eq!(result.principal_variation.len(), 3);
+    }
+
+    #[test]
+    fn test_repetition_is_a_draw() {
+        // Black is a queen down, Kg8 brings back the position before the last move.
+        let board = board_from("7k/8/8/8/8/8/8/1Q4K1 b - - 4 10");
+        let repeated = board_from("6k1/8/8/8/8/8/8/1Q4K1 w - - 3 10");
+
+        let lost = board.search(&depth_limit(4), &table());
+
+        assert!(lost.score < -500);
+
+        let limits = SearchLimits {
+            history: vec![repeated.zobrist_key],
+            ..depth_limit(4)
+        };
+        let result = board.search(&limits, &table());
+
+        assert_eq!(
+            result.best_move.map(|m| m.to_string()).as_deref(),
+            Some("h8g8")
+        );
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn test_sees_being_mated() {
+        let result = board_from(MATED_IN_ONE).search(&depth_limit(3), &table());
+
+        assert_eq!(result.mate_in(), Some(-1));
+    }
+
+    #[test]
+    fn test_mates_found_by_quiescence() {
+        // Checks are searched to the end past MAX_DEPTH, a mate there is still a mate.
+        let plies = MAX_DEPTH as i32 + 35;
+        let result = SearchResult {
+            best_move: None,
+            score: -(MATE_SCORE - plies),
+            depth: MAX_DEPTH,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            principal_variation: Vec::new(),
+        };
+
+        assert!(is_mate_score(result.score));
+        assert_eq!(result.mate_in(), Some(-50));
+        assert!(!is_mate_score(5_000));
+    }
+
+    #[test]
+    fn test_captures_hanging_queen() {
+        let result = board_from(HANGING_QUEEN).search(&depth_limit(3), &table());
+
+        assert_eq!(
+            result.best_move.map(|m| m.to_string()).as_deref(),
+            Some("d1d5")
+        );
+        assert!(result.score > 400);
+    }
+
+    #[test]
+    fn test_no_move_without_legal_moves() {
+        let result = board_from(STALEMATE).search(&depth_limit(3), &table());
+
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn test_reports_every_depth() {
+        let mut depths = Vec::new();
+        let table = TranspositionTable::new(1);
+
+        let result =
+            iterative_deepening(&ChessBoard::default(), &depth_limit(3), &table, |result| {
+                depths.push(result.depth);
+                assert_eq!(result.principal_variation.len() as u32, result.depth);
+            });
+
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert_eq!(result.depth, 3);
+    }
+
+    #[test]
+    fn test_node_and_time_limits() {
+        let limits = SearchLimits {
+            nodes: Some(2000),
+            ..Default::default()
+        };
+
+        let result = ChessBoard::default().search(&limits, &table());
+
+        assert!(result.nodes <= 2000);
+        assert!(result.best_move.is_some());
+
+        let limits = SearchLimits {
+            time: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let result = ChessBoard::default().search(&limits, &table());
+
+        assert!(start.elapsed() < Duration::from_millis(1000));
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_stop_flag() {
+        let limits = SearchLimits {
+            stop: Some(Arc::new(AtomicBool::new(true))),
+            ..Default::default()
+        };
+
+        let result = ChessBoard::default().search(&limits, &table());
+
+        assert!(result.best_move.is_some());
+        assert!(result.depth < 4);
+    }
+
+    fn feature_limit(depth: u32, features: SearchFeatures) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            features,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_quiescence_sees_recapture() {
+        let board = board_from(DEFENDED_KNIGHT);
+
+        // At the horizon the knight looks free without the quiescence search.
+        let horizon = board.search(&feature_limit(1, SearchFeatures::none()), &table());
+
+        assert_eq!(
+            horizon.best_move.map(|m| m.to_string()).as_deref(),
+            Some("d1d5")
+        );
+
+        let result = board.search(&depth_limit(1), &table());
+
+        assert_ne!(
+            result.best_move.map(|m| m.to_string()).as_deref(),
+            Some("d1d5")
+        );
+        assert!(result.score < horizon.score);
+    }
+
+    #[test]
+    fn test_each_feature_can_be_switched_off() {
+        let all = SearchFeatures::default();
+        let switched_off = [
+            SearchFeatures {
+                quiescence: false,
+                ..all
+            },
+            SearchFeatures {
+                see_pruning: false,
+                ..all
+            },
+            SearchFeatures {
+                mvv_lva: false,
+                ..all
+            },
+            SearchFeatures {
+                killer_moves: false,
+                ..all
+            },
+            SearchFeatures {
+                history: false,
+                ..all
+            },
+            SearchFeatures {
+                null_move_pruning: false,
+                ..all
+            },
+            SearchFeatures {
+                late_move_reductions: false,
+                ..all
+            },
+            SearchFeatures {
+                aspiration_windows: false,
+                ..all
+            },
+            SearchFeatures::none(),
+        ];
+
+        for features in switched_off {
+            let result = board_from(MATE_IN_TWO).search(&feature_limit(4, features), &table());
+
+            assert_eq!(result.mate_in(), Some(2), "{features:?}");
+            assert_eq!(
+                result.best_move.map(|m| m.to_string()).as_deref(),
+                Some("d2h6"),
+                "{features:?}"
+            );
+
+            let result = board_from(HANGING_QUEEN).search(&feature_limit(3, features), &table());
+
+            assert_eq!(
+                result.best_move.map(|m| m.to_string()).as_deref(),
+                Some("d1d5"),
+                "{features:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pruning_searches_fewer_nodes() {
+        let board = board_from(KIWI_PETE);
+        let without_pruning = SearchFeatures {
+            null_move_pruning: false,
+            late_move_reductions: false,
+            ..Default::default()
+        };
+
+        let pruned = board.search(&depth_limit(5), &table());
+        let full = board.search(&feature_limit(5, without_pruning), &table());
+
+        assert!(pruned.nodes < full.nodes, "{} {}", pruned.nodes, full.nodes);
+    }
+}
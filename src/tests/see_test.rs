@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitboard::{square_bit, squares},
+        engine::{ChessEngine, attackers_to},
+        moves::LegalMove,
+        tests::board_from,
+    };
+
+    fn see(fen: &str, uci_move: &str) -> i32 {
+        let board = board_from(fen);
+        let legal_move: LegalMove = board.parse_uci_move(uci_move).expect("Is legal");
+
+        board.see(legal_move)
+    }
+
+    #[test]
+    fn test_attackers_of_both_colours() {
+        let board = board_from("4k3/8/1n2p3/3r4/2B1P3/3Q1N2/8/3RK3 w - - 0 1");
+
+        // The rook on d5 is attacked by the d3 queen, c4 bishop and e4 pawn and defended by the b6 knight and e6
+        // pawn. The d1 rook stands behind the queen.
+        let attackers: Vec<usize> = squares(board.attackers_to(35)).collect();
+
+        assert_eq!(attackers, vec![19, 26, 28, 41, 44]);
+
+        let without_queen = board.bitboards.occupied ^ square_bit(19);
+        let attackers: Vec<usize> =
+            squares(attackers_to(&board.bitboards, 35, without_queen)).collect();
+
+        assert_eq!(attackers, vec![3, 19, 26, 28, 41, 44]);
+    }
+
+    #[test]
+    fn test_undefended_and_defended_captures() {
+        assert_eq!(
+            see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            100
+        );
+
+        // The pawn is defended by another pawn, taking it with the queen loses the queen for a pawn.
+        assert_eq!(see("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), 100 - 900);
+
+        // Taking a defended rook with a knight still wins the exchange.
+        assert_eq!(
+            see("4k3/8/4p3/3r4/8/4N3/8/4K3 w - - 0 1", "e3d5"),
+            500 - 320
+        );
+    }
+
+    #[test]
+    fn test_x_ray_attackers() {
+        // The second rook joins in behind the first, so taking the defended pawn wins it.
+        assert_eq!(see("4k3/4r3/8/4p3/8/8/4R3/4RK2 w - - 0 1", "e2e5"), 100);
+
+        // Without it the rook is lost for a pawn.
+        assert_eq!(
+            see("4k3/4r3/8/4p3/8/8/4R3/5K2 w - - 0 1", "e2e5"),
+            100 - 500
+        );
+
+        // The black queen behind the rook defends through it.
+        assert_eq!(
+            see("4k3/4q3/4r3/4p3/8/8/4R3/4RK2 w - - 0 1", "e2e5"),
+            100 - 500
+        );
+    }
+
+    #[test]
+    fn test_king_only_takes_undefended_pieces() {
+        // The king recaptures, unless the piece it would take is defended.
+        assert_eq!(see("8/8/8/8/4k3/8/3n4/4KB2 b - - 0 1", "d2f1"), 330 - 320);
+        assert_eq!(see("5r2/8/8/8/4k3/8/3n4/4KB2 b - - 0 1", "d2f1"), 330);
+        assert_eq!(see("8/8/8/3k4/4n3/8/6B1/7K w - - 0 1", "g2e4"), 320 - 330);
+        assert_eq!(see("8/8/8/3k4/4n3/8/6B1/4R2K w - - 0 1", "g2e4"), 320);
+    }
+
+    #[test]
+    fn test_special_moves() {
+        assert_eq!(see("4k3/8/8/Pp6/8/8/8/4K3 w - b6 0 1", "a5b6"), 100);
+
+        // Promoting on a defended square gives up the new queen, but takes the rook first.
+        assert_eq!(see("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"), 500 + 800);
+        assert_eq!(
+            see("r2qk3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"),
+            500 + 800 - 900
+        );
+
+        // A quiet move onto an attacked square loses the piece.
+        assert_eq!(see("4k3/8/8/1p6/8/8/8/2R1K3 w - - 0 1", "c1c4"), -500);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::chess_game::should_be_light_square;
+    use crate::chessboard::should_be_light_square;
 
     #[test]
     fn test_square_is_white_1() {
@@ -8,7 +8,7 @@ mod tests {
 
         let is_white = should_be_light_square(pos);
 
-        assert_eq!(true, is_white)
+        assert!(is_white)
     }
 
     #[test]
@@ -17,7 +17,7 @@ mod tests {
 
         let is_white = should_be_light_square(pos);
 
-        assert_eq!(false, is_white)
+        assert!(!is_white)
     }
 
     #[test]
@@ -26,7 +26,7 @@ mod tests {
 
         let is_white = should_be_light_square(pos);
 
-        assert_eq!(true, is_white)
+        assert!(is_white)
     }
 
     #[test]
@@ -35,6 +35,6 @@ mod tests {
 
         let is_white = should_be_light_square(pos);
 
-        assert_eq!(false, is_white)
+        assert!(!is_white)
     }
 }
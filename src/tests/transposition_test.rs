@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine::ChessEngine,
+        search::{MATE_SCORE, SearchLimits, iterative_deepening},
+        tests::board_from,
+        transposition::{Bound, PerftTable, TranspositionTable},
+    };
+
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+    const PERFT_POS_1: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const MATE_IN_TWO: &str = "r1bq2r1/b4pk1/p1pp1p2/1p2pP2/1P2P1PB/3P4/1PPQ2P1/R3K2R w - - 0 1";
+
+    #[test]
+    fn test_store_and_probe() {
+        let table = TranspositionTable::new(1);
+        let board = board_from(KIWI_PETE);
+        let legal_moves = board.legal_moves();
+        let best_move = legal_moves[3];
+
+        assert_eq!(table.probe(board.zobrist_key), None);
+
+        for score in [0, 35, -1200, MATE_SCORE - 3, -MATE_SCORE + 8] {
+            table.store(board.zobrist_key, 7, score, Bound::Lower, Some(best_move));
+
+            let entry = table.probe(board.zobrist_key).expect("Was just stored");
+
+            assert_eq!(entry.depth, 7);
+            assert_eq!(entry.score, score);
+            assert_eq!(entry.bound, Bound::Lower);
+            assert_eq!(entry.best_move(&legal_moves), Some(best_move));
+        }
+
+        // A new entry without a move keeps the move that was found before.
+        table.store(board.zobrist_key, 2, 10, Bound::Upper, None);
+
+        let entry = table.probe(board.zobrist_key).expect("Was just stored");
+
+        assert_eq!((entry.depth, entry.bound), (2, Bound::Upper));
+        assert_eq!(entry.best_move(&legal_moves), Some(best_move));
+
+        table.clear();
+
+        assert_eq!(table.probe(board.zobrist_key), None);
+    }
+
+    #[test]
+    fn test_promotion_moves_are_kept_apart() {
+        let board = board_from("8/1P6/8/8/8/8/6k1/4K3 w - - 0 1");
+        let legal_moves = board.legal_moves();
+        let table = TranspositionTable::new(1);
+
+        for promotion in legal_moves
+            .iter()
+            .filter(|legal_move| legal_move.from == 49)
+        {
+            table.store(board.zobrist_key, 1, 0, Bound::Exact, Some(*promotion));
+
+            let entry = table.probe(board.zobrist_key).expect("Was just stored");
+
+            assert_eq!(entry.best_move(&legal_moves), Some(*promotion));
+        }
+    }
+
+    #[test]
+    fn test_replacement_prefers_deep_and_recent_entries() {
+        // The smallest table has a single bucket of four slots, so every key competes for it.
+        let table = TranspositionTable::new(0);
+
+        for key in 1..=4 {
+            table.store(key, key as u32 + 10, 0, Bound::Exact, None);
+        }
+
+        table.store(5, 20, 0, Bound::Exact, None);
+
+        assert_eq!(table.probe(1), None);
+        assert!((2..=5).all(|key| table.probe(key).is_some()));
+
+        // Entries of earlier searches are replaced before a shallower entry of the current one.
+        table.new_search();
+        table.new_search();
+        table.store(6, 1, 0, Bound::Exact, None);
+        table.store(7, 1, 0, Bound::Exact, None);
+
+        assert_eq!(table.probe(2), None);
+        assert_eq!(table.probe(3), None);
+        assert!(table.probe(6).is_some() && table.probe(7).is_some());
+    }
+
+    #[test]
+    fn test_hashed_perft_matches_perft() {
+        let table = PerftTable::new(4);
+
+        for (fen, depth) in [(KIWI_PETE, 3), (PERFT_POS_1, 4)] {
+            let board = board_from(fen);
+
+            assert_eq!(board.perft_with_table(depth, &table), board.perft(depth));
+        }
+
+        // The second run is answered from the table.
+        let board = board_from(KIWI_PETE);
+
+        assert_eq!(board.perft_with_table(3, &table).1, 97862);
+    }
+
+    #[test]
+    fn test_search_reuses_table() {
+        let board = board_from(MATE_IN_TWO);
+        let table = TranspositionTable::new(1);
+        let limits = SearchLimits {
+            depth: Some(4),
+            ..Default::default()
+        };
+
+        let first = iterative_deepening(&board, &limits, &table, |_| {});
+        let second = iterative_deepening(&board, &limits, &table, |_| {});
+
+        assert_eq!(first.mate_in(), Some(2));
+        assert_eq!(second.mate_in(), Some(2));
+        assert_eq!(second.best_move, first.best_move);
+        assert!(second.nodes < first.nodes);
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        chessboard::{BQUEEN, ChessBoard, WKNIGHT, WQUEEN},
+        engine::ChessEngine,
+        fen::Fen,
+        moves::{LegalMove, LegalMoveParseError, LegalMoveParseErrorKind, MoveType},
+    };
+
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+    const PROMOTION_POS: &str = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    const EN_PASSANT_POS: &str = "4k3/8/8/Pp6/8/8/8/4K3 w - b6 0 1";
+
+    fn error(kind: LegalMoveParseErrorKind, given: &str) -> LegalMoveParseError {
+        LegalMoveParseError::new(kind, given.to_string())
+    }
+
+    #[test]
+    fn test_from_str_normal() {
+        let parsed = LegalMove::from_str("e2e4").expect("Is valid UCI");
+
+        assert_eq!(parsed.from, 12);
+        assert_eq!(parsed.to, 28);
+        assert_eq!(parsed.move_type, MoveType::Normal);
+    }
+
+    #[test]
+    fn test_from_str_promotion_color() {
+        let white = LegalMove::from_str("e7e8q").expect("Is valid UCI");
+        let black = LegalMove::from_str("e2e1q").expect("Is valid UCI");
+
+        assert_eq!(
+            white.move_type,
+            MoveType::PawnMove {
+                promotion_move: Some(WQUEEN)
+            }
+        );
+        assert_eq!(
+            black.move_type,
+            MoveType::PawnMove {
+                promotion_move: Some(BQUEEN)
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_str_malformed() {
+        for malformed in [
+            "", "e2", "e2e9", "i2e4", "e2e4x", "e4e5q", "e7e8k", "e2e4qq",
+        ] {
+            assert_eq!(
+                LegalMove::from_str(malformed),
+                Err(error(LegalMoveParseErrorKind::Malformed, malformed))
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip_legal_moves() {
+        let board = ChessBoard::set_fen_position(KIWI_PETE).expect("Is valid FEN");
+
+        for legal_move in board.legal_moves() {
+            assert_eq!(
+                board.parse_uci_move(&legal_move.to_string()),
+                Ok(legal_move)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_castling() {
+        let board = ChessBoard::set_fen_position(KIWI_PETE).expect("Is valid FEN");
+
+        let castle = board.parse_uci_move("e1g1").expect("Castling is legal");
+
+        assert_eq!(castle.move_type, MoveType::CastleKingSide);
+    }
+
+    #[test]
+    fn test_parse_en_passant() {
+        let board = ChessBoard::set_fen_position(EN_PASSANT_POS).expect("Is valid FEN");
+
+        let en_passant = board.parse_uci_move("a5b6").expect("En passant is legal");
+
+        assert_eq!(
+            en_passant.move_type,
+            MoveType::Enpassant { target_square: 33 }
+        );
+        assert!(en_passant.is_capture);
+    }
+
+    #[test]
+    fn test_parse_promotions() {
+        let board = ChessBoard::set_fen_position(PROMOTION_POS).expect("Is valid FEN");
+
+        let capture = board.parse_uci_move("a7b8n").expect("Promotion is legal");
+
+        assert_eq!(
+            capture.move_type,
+            MoveType::PawnMove {
+                promotion_move: Some(WKNIGHT)
+            }
+        );
+        assert!(capture.is_capture);
+
+        assert_eq!(
+            board.parse_uci_move("a7a8"),
+            Err(error(LegalMoveParseErrorKind::MissingPromotion, "a7a8"))
+        );
+    }
+
+    #[test]
+    fn test_parse_illegal() {
+        let board = ChessBoard::default();
+
+        for illegal in ["e2e5", "e7e5", "e1g1", "b1d2"] {
+            assert_eq!(
+                board.parse_uci_move(illegal),
+                Err(error(LegalMoveParseErrorKind::Illegal, illegal))
+            );
+        }
+    }
+}
@@ -0,0 +1,274 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{Receiver, channel};
+
+    use crate::uci::{GoCommand, UciEngine};
+
+    fn new_engine() -> (UciEngine, Receiver<String>) {
+        let (output, receiver) = channel();
+        (UciEngine::new(output), receiver)
+    }
+
+    fn run(engine: &mut UciEngine, receiver: &Receiver<String>, commands: &[&str]) -> Vec<String> {
+        for command in commands {
+            engine.handle_command(command);
+        }
+        receiver.try_iter().collect()
+    }
+
+    #[test]
+    fn test_parse_go() {
+        let go = GoCommand::parse(&[
+            "wtime",
+            "300000",
+            "btime",
+            "290000",
+            "winc",
+            "2000",
+            "binc",
+            "2000",
+            "movestogo",
+            "40",
+        ]);
+
+        assert_eq!(
+            go,
+            GoCommand {
+                wtime: Some(300000),
+                btime: Some(290000),
+                winc: Some(2000),
+                binc: Some(2000),
+                movestogo: Some(40),
+                ..Default::default()
+            }
+        );
+
+        let go = GoCommand::parse(&["infinite"]);
+        assert!(go.infinite);
+
+        let go = GoCommand::parse(&["depth", "6", "nodes", "1000", "movetime", "50"]);
+        assert_eq!(go.depth, Some(6));
+        assert_eq!(go.nodes, Some(1000));
+        assert_eq!(go.movetime, Some(50));
+    }
+
+    #[test]
+    fn test_handshake() {
+        let (mut engine, receiver) = new_engine();
+
+        let output = run(&mut engine, &receiver, &["uci", "isready"]);
+
+        assert!(output[0].starts_with("id name"));
+        assert_eq!(output[output.len() - 2], "uciok");
+        assert_eq!(output[output.len() - 1], "readyok");
+    }
+
+    #[test]
+    fn test_go_perft_from_position() {
+        let (mut engine, receiver) = new_engine();
+
+        let output = run(
+            &mut engine,
+            &receiver,
+            &[
+                "position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "go perft 2",
+            ],
+        );
+
+        assert_eq!(output.len(), 48 + 3);
+        assert!(output.contains(&String::from("e1g1: 43")));
+        assert!(output.contains(&String::from("Nodes searched: 2039")));
+
+        let output = run(
+            &mut engine,
+            &receiver,
+            &["position startpos moves e2e4 e7e5 g1f3", "go perft 1"],
+        );
+
+        assert_eq!(output.len(), 29 + 3);
+    }
+
+    #[test]
+    fn test_go_sends_best_move() {
+        let (mut engine, receiver) = new_engine();
+
+        engine.handle_command("position startpos moves f2f3 e7e5 g2g4");
+        engine.handle_command("go depth 2");
+
+        let info = receiver.recv().expect("Sends info");
+        let best_move = receiver.recv().expect("Sends bestmove");
+
+        assert!(info.starts_with("info depth 1 score mate 1 "));
+        assert!(info.ends_with(" pv d8h4"));
+        assert_eq!(best_move, "bestmove d8h4");
+    }
+
+    #[test]
+    fn test_search_knows_the_moves_played() {
+        let (mut engine, receiver) = new_engine();
+
+        // Black is a queen down, Kg8 repeats the position the moves started from.
+        engine.handle_command("position fen 6k1/8/8/8/8/8/8/1Q4K1 w - - 0 1 moves b1c1 g8h8 c1b1");
+        engine.handle_command("go depth 4");
+
+        let mut output = Vec::new();
+
+        while let Ok(line) = receiver.recv() {
+            let done = line.starts_with("bestmove ");
+            output.push(line);
+
+            if done {
+                break;
+            }
+        }
+
+        assert_eq!(output.last().map(String::as_str), Some("bestmove h8g8"));
+        assert!(output[output.len() - 2].contains(" score cp 0 "));
+    }
+
+    #[test]
+    fn test_go_infinite_waits_for_stop() {
+        let (mut engine, receiver) = new_engine();
+
+        engine.handle_command("go infinite");
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        assert!(
+            receiver
+                .try_iter()
+                .all(|line| line.starts_with("info depth"))
+        );
+
+        engine.handle_command("stop");
+
+        let output: Vec<String> = receiver.try_iter().collect();
+
+        assert!(
+            output
+                .last()
+                .expect("Sent on stop")
+                .starts_with("bestmove ")
+        );
+    }
+
+    #[test]
+    fn test_setoption() {
+        let (mut engine, receiver) = new_engine();
+
+        run(
+            &mut engine,
+            &receiver,
+            &[
+                "setoption name Clear Hash",
+                "setoption name Move Overhead value 30",
+            ],
+        );
+
+        assert_eq!(engine.option("Clear Hash"), Some(""));
+        assert_eq!(engine.option("Move Overhead"), Some("30"));
+    }
+
+    #[test]
+    fn test_hash_option() {
+        let (mut engine, receiver) = new_engine();
+
+        let output = run(&mut engine, &receiver, &["uci"]);
+
+        assert!(output.contains(&String::from(
+            "option name Hash type spin default 16 min 1 max 4096"
+        )));
+        assert!(output.contains(&String::from("option name Clear Hash type button")));
+
+        let output = run(
+            &mut engine,
+            &receiver,
+            &[
+                "setoption name Hash value 2",
+                "setoption name Hash value lots",
+                "ucinewgame",
+                "isready",
+            ],
+        );
+
+        assert_eq!(engine.option("Hash"), Some("lots"));
+        assert_eq!(
+            output,
+            vec!["info string invalid Hash value: lots", "readyok"]
+        );
+    }
+
+    #[test]
+    fn test_chess960_castling_notation() {
+        let (mut engine, receiver) = new_engine();
+        let position = "position fen 1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R4KR w HBhb - 0 1 moves g1h1";
+
+        let output = run(&mut engine, &receiver, &[position]);
+
+        assert_eq!(
+            output,
+            vec!["info string 'g1h1' is not legal in this position"]
+        );
+
+        let output = run(
+            &mut engine,
+            &receiver,
+            &["setoption name UCI_Chess960 value true", position, "d"],
+        );
+
+        assert!(
+            output[0].ends_with("Fen: 1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 b kq - 1 1"),
+            "{output:?}"
+        );
+    }
+
+    #[test]
+    fn test_position_without_kings() {
+        let (mut engine, receiver) = new_engine();
+
+        let output = run(
+            &mut engine,
+            &receiver,
+            &["position fen 8/8/8/8/8/8/8/8 w - - 0 1"],
+        );
+
+        assert_eq!(
+            output,
+            vec!["info string invalid fen 8/8/8/8/8/8/8/8 w - - 0 1: InvalidArgument(Position)"]
+        );
+    }
+
+    #[test]
+    fn test_position_with_a_king_to_capture() {
+        let (mut engine, receiver) = new_engine();
+
+        let output = run(
+            &mut engine,
+            &receiver,
+            &["position fen 4k3/4R3/8/8/8/8/8/4K3 w - - 0 1"],
+        );
+
+        assert_eq!(
+            output,
+            vec!["info string invalid fen 4k3/4R3/8/8/8/8/8/4K3 w - - 0 1: SideNotToMoveInCheck"]
+        );
+
+        // The search still answers, from the position before.
+        engine.handle_command("go depth 1");
+
+        assert!(receiver.iter().any(|line| line.starts_with("bestmove ")));
+    }
+
+    #[test]
+    fn test_illegal_move_and_quit() {
+        let (mut engine, receiver) = new_engine();
+
+        let output = run(&mut engine, &receiver, &["position startpos moves e2e5"]);
+
+        assert_eq!(
+            output,
+            vec!["info string 'e2e5' is not legal in this position"]
+        );
+        assert!(!engine.handle_command("quit"));
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use crate::{chessboard::ChessBoard, engine::ChessEngine, fen::Fen, zobrist::zobrist_key};
+
+    const KIWI_PETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const EN_PASSANT: &str = "4k3/8/8/Pp6/8/8/8/4K3 w - b6 0 1";
+
+    /* The example keys from the Polyglot book format description, each position follows from the one before. */
+    const POLYGLOT_KEYS: [(&str, &str, u64); 9] = [
+        (
+            "",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            0x463b96181691fc9c,
+        ),
+        (
+            "e2e4",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            0x823c9b50fd114196,
+        ),
+        (
+            "d7d5",
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+            0x0756b94461c50fb0,
+        ),
+        (
+            "e4e5",
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+            0x662fafb965db29d4,
+        ),
+        (
+            "f7f5",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            0x22a48b5a8e47ff78,
+        ),
+        (
+            "e1e2",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3",
+            0x652a607ca3f242c1,
+        ),
+        (
+            "e8f7",
+            "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4",
+            0x00fdd303c946bdd9,
+        ),
+        (
+            "",
+            "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+            0x3c8123ea7b067637,
+        ),
+        (
+            "b4c3 a1a3",
+            "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4",
+            0x5c3f9b829b279560,
+        ),
+    ];
+
+    #[test]
+    fn test_polyglot_keys_from_fen() {
+        for (_, fen, key) in POLYGLOT_KEYS {
+            let chessboard = ChessBoard::set_fen_position(fen).expect("Is valid FEN");
+
+            assert_eq!(chessboard.zobrist_key, key, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_polyglot_keys_from_moves() {
+        let mut chessboard = ChessBoard::default();
+
+        for (moves, fen, key) in POLYGLOT_KEYS {
+            if moves.is_empty() {
+                chessboard = ChessBoard::set_fen_position(fen).expect("Is valid FEN");
+            }
+
+            for uci_move in moves.split_whitespace() {
+                let legal_move = chessboard.parse_uci_move(uci_move).expect("Is legal");
+                chessboard.make_move(legal_move);
+            }
+
+            assert_eq!(chessboard.to_fen(), fen);
+            assert_eq!(chessboard.zobrist_key, key, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_en_passant_only_hashed_when_capturable() {
+        // After a4 no black pawn stands next to it, so the key is the same as without the en passant square.
+        let with_square =
+            ChessBoard::set_fen_position("4k3/8/8/8/P7/8/8/4K3 b - a3 0 1").expect("Is valid FEN");
+        let without_square =
+            ChessBoard::set_fen_position("4k3/8/8/8/P7/8/8/4K3 b - - 0 1").expect("Is valid FEN");
+
+        assert_eq!(with_square.zobrist_key, without_square.zobrist_key);
+
+        let capturable = ChessBoard::set_fen_position(EN_PASSANT).expect("Is valid FEN");
+        let not_capturable =
+            ChessBoard::set_fen_position("4k3/8/8/Pp6/8/8/8/4K3 w - - 0 1").expect("Is valid FEN");
+
+        assert_ne!(capturable.zobrist_key, not_capturable.zobrist_key);
+    }
+
+    fn assert_incremental_key(chessboard: &mut ChessBoard, depth: u32) {
+        assert_eq!(
+            chessboard.zobrist_key,
+            zobrist_key(chessboard),
+            "{}",
+            chessboard.to_fen()
+        );
+
+        if depth == 0 {
+            return;
+        }
+
+        for legal_move in chessboard.legal_moves() {
+            let saved_state = chessboard.make_move(legal_move);
+            assert_incremental_key(chessboard, depth - 1);
+            chessboard.unmake_move(legal_move, saved_state);
+        }
+    }
+
+    #[test]
+    fn test_incremental_key_matches_recomputed_key() {
+        for fen in [KIWI_PETE, EN_PASSANT] {
+            let mut chessboard = ChessBoard::set_fen_position(fen).expect("Is valid FEN");
+
+            assert_incremental_key(&mut chessboard, 3);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use crate::moves::{LegalMove, MoveType};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+pub const DEFAULT_HASH_MB: usize = 16;
+pub const MAX_HASH_MB: usize = 4096;
+
+/* A bucket fills one cache line, so probing a position touches a single line of memory. */
+const SLOTS_PER_BUCKET: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower, /* The search failed high, the score is at least this much */
+    Upper, /* The search failed low, the score is at most this much */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TranspositionEntry {
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub age: u8, /* The search the entry was stored in, see TranspositionTable::new_search */
+    best_move: u16,
+}
+
+impl TranspositionEntry {
+    /*
+     * Only the squares and the promotion of the best move are stored, so it is looked up among the legal moves.
+     * A move that is not legal, because another position shares the slot, is never returned.
+     */
+    pub fn best_move(&self, legal_moves: &[LegalMove]) -> Option<LegalMove> {
+        if self.best_move == 0 {
+            return None;
+        }
+
+        legal_moves
+            .iter()
+            .find(|legal_move| pack_move(legal_move) == self.best_move)
+            .copied()
+    }
+
+    /*
+     * Bits 0-15 hold the move, 16-39 the score, 40-47 the depth, 48-49 the bound and 50-57 the age.
+     */
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+
+        self.best_move as u64
+            | ((self.score as u64 & 0xff_ffff) << 16)
+            | ((self.depth.min(u8::MAX as u32) as u64) << 40)
+            | (bound << 48)
+            | ((self.age as u64) << 50)
+    }
+
+    fn unpack(data: u64) -> Self {
+        let bound = match (data >> 48) & 0b11 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+
+        Self {
+            depth: ((data >> 40) & 0xff) as u32,
+            // Shift the 24 bit score to the top and back down to restore its sign.
+            score: ((data << 24) as i64 >> 40) as i32,
+            bound,
+            age: (data >> 50) as u8,
+            best_move: data as u16,
+        }
+    }
+}
+
+/* from | to << 6 | promotion << 12, a1a1 can never be played so 0 means no move. */
+fn pack_move(legal_move: &LegalMove) -> u16 {
+    let promotion = match legal_move.move_type {
+        MoveType::PawnMove {
+            promotion_move: Some(piece),
+        } => piece.unsigned_abs() as u16,
+        _ => 0,
+    };
+
+    legal_move.from as u16 | ((legal_move.to as u16) << 6) | (promotion << 12)
+}
+
+/*
+ * A slot stores the key xor the data next to the data, so an entry torn apart by two threads writing at once
+ * fails the key check instead of being read as a wrong entry. That keeps the table usable without locks.
+ * Stored entries always have a depth of at least 1, so data 0 marks an empty slot.
+ */
+#[derive(Debug, Default)]
+struct Slot {
+    checked_key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+
+        (self.checked_key.load(Ordering::Relaxed) ^ data, data)
+    }
+
+    fn store(&self, key: u64, data: u64) {
+        self.checked_key.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+
+    fn clear(&self) {
+        self.checked_key.store(0, Ordering::Relaxed);
+        self.data.store(0, Ordering::Relaxed);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.load(Ordering::Relaxed) == 0
+    }
+}
+
+#[derive(Debug, Default)]
+#[repr(align(64))]
+struct Bucket([Slot; SLOTS_PER_BUCKET]);
+
+/*
+ * A fixed size hash table of search results indexed by zobrist key. All methods take &self, so one table can be
+ * shared between threads, e.g. behind an Arc.
+ */
+#[derive(Debug)]
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let bucket_count = (size_mb * 1024 * 1024 / size_of::<Bucket>()).max(1);
+
+        Self {
+            buckets: (0..bucket_count).map(|_| Bucket::default()).collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    pub fn clear(&self) {
+        for bucket in &self.buckets {
+            bucket.0.iter().for_each(Slot::clear);
+        }
+
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /*
+     * Called at the start of every search, so entries of earlier searches are replaced first.
+     */
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TranspositionEntry> {
+        self.bucket(key).0.iter().find_map(|slot| {
+            let (slot_key, data) = slot.load();
+
+            (slot_key == key && data != 0).then(|| TranspositionEntry::unpack(data))
+        })
+    }
+
+    /*
+     * The slot of the same position is always overwritten, keeping its move if there is no new one. Otherwise the
+     * entry that is least worth keeping goes: empty slots first, then the shallowest entries from the oldest
+     * searches.
+     */
+    pub fn store(
+        &self,
+        key: u64,
+        depth: u32,
+        score: i32,
+        bound: Bound,
+        best_move: Option<LegalMove>,
+    ) {
+        let age = self.age.load(Ordering::Relaxed);
+        let bucket = self.bucket(key);
+
+        let mut entry = TranspositionEntry {
+            depth,
+            score,
+            bound,
+            age,
+            best_move: best_move.as_ref().map_or(0, pack_move),
+        };
+
+        let same_position = bucket.0.iter().find(|slot| slot.load().0 == key);
+
+        let slot = match same_position {
+            Some(slot) => {
+                if entry.best_move == 0 {
+                    entry.best_move = TranspositionEntry::unpack(slot.load().1).best_move;
+                }
+                slot
+            }
+            None => bucket
+                .0
+                .iter()
+                .min_by_key(|slot| {
+                    if slot.is_empty() {
+                        return i32::MIN;
+                    }
+
+                    let stored = TranspositionEntry::unpack(slot.load().1);
+                    let searches_ago = age.wrapping_sub(stored.age) as i32;
+
+                    stored.depth as i32 - 8 * searches_ago
+                })
+                .expect("A bucket has slots"),
+        };
+
+        slot.store(key, entry.pack());
+    }
+
+    fn bucket(&self, key: u64) -> &Bucket {
+        // Map the key onto the buckets with a multiplication, which works for any number of buckets.
+        let index = ((key as u128 * self.buckets.len() as u128) >> 64) as usize;
+
+        &self.buckets[index]
+    }
+}
+
+/*
+ * Node counts of perft subtrees by zobrist key and depth. Every key has one slot that is always replaced.
+ */
+#[derive(Debug)]
+pub struct PerftTable {
+    slots: Vec<Slot>,
+}
+
+impl PerftTable {
+    pub fn new(size_mb: usize) -> Self {
+        let slot_count = (size_mb * 1024 * 1024 / size_of::<Slot>()).max(1);
+
+        Self {
+            slots: (0..slot_count).map(|_| Slot::default()).collect(),
+        }
+    }
+
+    /* The data is the node count above the depth in the low byte. */
+    pub fn probe(&self, key: u64, depth: u64) -> Option<u64> {
+        let (slot_key, data) = self.slot(key).load();
+
+        (slot_key == key && data & 0xff == depth && data != 0).then_some(data >> 8)
+    }
+
+    pub fn store(&self, key: u64, depth: u64, nodes: u64) {
+        self.slot(key).store(key, (nodes << 8) | depth);
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        let index = ((key as u128 * self.slots.len() as u128) >> 64) as usize;
+
+        &self.slots[index]
+    }
+}
@@ -0,0 +1,433 @@
+use crate::chessboard::{ChessBoard, Players};
+use crate::engine::ChessEngine;
+use crate::fen::Fen;
+use crate::moves::LegalMove;
+use crate::search::{MAX_DEPTH, SearchFeatures, SearchLimits, SearchResult, iterative_deepening};
+use crate::transposition::{DEFAULT_HASH_MB, MAX_HASH_MB, TranspositionTable};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Sender, channel};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/*
+ * The parameters of a "go" command. Times are in milliseconds.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoCommand {
+    pub depth: Option<u64>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub infinite: bool,
+    pub perft: Option<u64>,
+}
+
+impl GoCommand {
+    pub fn parse(arguments: &[&str]) -> Self {
+        let mut go = Self::default();
+        let mut arguments = arguments.iter();
+
+        while let Some(argument) = arguments.next() {
+            let mut value = || arguments.next().and_then(|value| value.parse().ok());
+
+            match *argument {
+                "depth" => go.depth = value(),
+                "nodes" => go.nodes = value(),
+                "movetime" => go.movetime = value(),
+                "wtime" => go.wtime = value(),
+                "btime" => go.btime = value(),
+                "winc" => go.winc = value(),
+                "binc" => go.binc = value(),
+                "movestogo" => go.movestogo = value(),
+                "perft" => go.perft = value(),
+                "infinite" => go.infinite = true,
+                _ => {}
+            }
+        }
+
+        go
+    }
+
+    /*
+     * Turn the go parameters into search limits. With a clock, spend an even share of the remaining time
+     * over the moves left (30 if unknown) plus most of the increment, but never more than half the clock.
+     */
+    pub fn search_limits(&self, side_to_move: Players, stop: Arc<AtomicBool>) -> SearchLimits {
+        let (time_left, increment) = match side_to_move {
+            Players::White => (self.wtime, self.winc.unwrap_or(0)),
+            Players::Black => (self.btime, self.binc.unwrap_or(0)),
+        };
+
+        let time = if self.infinite {
+            None
+        } else if let Some(movetime) = self.movetime {
+            Some(movetime)
+        } else {
+            time_left.map(|time_left| {
+                let moves_to_go = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let share = time_left / moves_to_go + increment * 3 / 4;
+
+                share
+                    .min(time_left / 2)
+                    .saturating_sub(MOVE_OVERHEAD)
+                    .max(1)
+            })
+        };
+
+        SearchLimits {
+            depth: self.depth.map(|depth| depth.min(MAX_DEPTH as u64) as u32),
+            nodes: self.nodes,
+            time: time.map(Duration::from_millis),
+            stop: Some(stop),
+            features: SearchFeatures::default(),
+            history: Vec::new(),
+        }
+    }
+}
+
+/* Milliseconds kept in reserve for communication with the GUI. */
+const MOVE_OVERHEAD: u64 = 20;
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/*
+ * A UCI engine session. Every line of output is sent through the channel, searches run on their own thread
+ * so "stop", "isready" and "quit" can be answered while searching.
+ */
+pub struct UciEngine {
+    board: ChessBoard,
+    history: Vec<u64>, /* Zobrist keys of the positions before the current one, for repetitions in the search */
+    options: HashMap<String, String>,
+    transposition_table: Arc<TranspositionTable>, /* Shared with the search thread */
+    output: Sender<String>,
+    stop: Arc<AtomicBool>,
+    search_thread: Option<JoinHandle<()>>,
+}
+
+impl UciEngine {
+    pub fn new(output: Sender<String>) -> Self {
+        Self {
+            board: ChessBoard::default(),
+            history: Vec::new(),
+            options: HashMap::new(),
+            transposition_table: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            output,
+            stop: Arc::new(AtomicBool::new(false)),
+            search_thread: None,
+        }
+    }
+
+    fn send(&self, line: String) {
+        // The receiver is only gone once the session is over, so there is nobody left to tell.
+        _ = self.output.send(line);
+    }
+
+    /*
+     * Handle one line of input. Returns false once the engine should quit.
+     */
+    pub fn handle_command(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let Some((command, arguments)) = words.split_first() else {
+            return true;
+        };
+
+        match *command {
+            "uci" => {
+                self.send(format!("id name {}", env!("CARGO_PKG_NAME")));
+                self.send(String::from("id author the chess developers"));
+                self.send(format!(
+                    "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                ));
+                self.send(String::from("option name Clear Hash type button"));
+                self.send(String::from(
+                    "option name UCI_Chess960 type check default false",
+                ));
+                self.send(String::from("uciok"));
+            }
+            "isready" => self.send(String::from("readyok")),
+            "ucinewgame" => {
+                self.stop_search();
+                self.board = ChessBoard::default();
+                self.history.clear();
+                self.transposition_table.clear();
+            }
+            "setoption" => self.set_option(arguments),
+            "position" => {
+                self.stop_search();
+                self.set_position(arguments);
+            }
+            "go" => {
+                self.stop_search();
+                self.go(GoCommand::parse(arguments));
+            }
+            "stop" => self.stop_search(),
+            "quit" => {
+                self.stop_search();
+                return false;
+            }
+            "d" => self.send(format!("{}\nFen: {}", self.board, self.board.to_fen())),
+            _ => self.send(format!("info string unknown command: {line}")),
+        }
+
+        true
+    }
+
+    /*
+     * setoption name <id> [value <x>], where both the name and the value may contain spaces.
+     */
+    fn set_option(&mut self, arguments: &[&str]) {
+        let value_index = arguments.iter().position(|word| *word == "value");
+
+        let (name, value) = match value_index {
+            Some(index) => (&arguments[..index], arguments[index + 1..].join(" ")),
+            None => (arguments, String::new()),
+        };
+
+        match name.split_first() {
+            Some((&"name", name)) if !name.is_empty() => {
+                let name = name.join(" ");
+
+                match name.as_str() {
+                    "Hash" => self.set_hash_size(&value),
+                    "Clear Hash" => self.transposition_table.clear(),
+                    _ => {}
+                }
+
+                self.options.insert(name, value);
+            }
+            _ => self.send(format!(
+                "info string invalid setoption: {}",
+                arguments.join(" ")
+            )),
+        }
+    }
+
+    /*
+     * Replace the table with one of the given size in MB, a running search keeps the old one until it is done.
+     */
+    fn set_hash_size(&mut self, value: &str) {
+        match value.parse::<usize>() {
+            Ok(size_mb) => {
+                let size_mb = size_mb.clamp(1, MAX_HASH_MB);
+                self.transposition_table = Arc::new(TranspositionTable::new(size_mb));
+            }
+            Err(_) => self.send(format!("info string invalid Hash value: {value}")),
+        }
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /* Under UCI_Chess960 castling moves are sent and received as the king taking its own rook. */
+    fn chess960(&self) -> bool {
+        self.option("UCI_Chess960") == Some("true")
+    }
+
+    /*
+     * position [fen <fen> | startpos] [moves <move1> ... <movei>]
+     */
+    fn set_position(&mut self, arguments: &[&str]) {
+        let moves_index = arguments
+            .iter()
+            .position(|word| *word == "moves")
+            .unwrap_or(arguments.len());
+
+        let board = match arguments.split_first() {
+            Some((&"startpos", _)) => ChessBoard::default(),
+            Some((&"fen", fen)) => {
+                let fen = fen[..moves_index - 1].join(" ");
+
+                match ChessBoard::set_fen_position(&fen) {
+                    Ok(board) => board,
+                    Err(e) => {
+                        self.send(format!("info string invalid fen {fen}: {:?}", e.kind));
+                        return;
+                    }
+                }
+            }
+            _ => {
+                self.send(format!(
+                    "info string invalid position: {}",
+                    arguments.join(" ")
+                ));
+                return;
+            }
+        };
+
+        self.board = board;
+        self.history.clear();
+
+        for uci_move in arguments.iter().skip(moves_index + 1) {
+            let parsed_move = if self.chess960() {
+                self.board.parse_chess960_uci_move(uci_move)
+            } else {
+                self.board.parse_uci_move(uci_move)
+            };
+
+            match parsed_move {
+                Ok(legal_move) => {
+                    self.history.push(self.board.zobrist_key);
+                    self.board.make_move(legal_move);
+                }
+                Err(e) => {
+                    self.send(format!("info string {e}"));
+                    return;
+                }
+            }
+        }
+    }
+
+    fn go(&mut self, go: GoCommand) {
+        if let Some(depth) = go.perft {
+            self.perft(depth);
+            return;
+        }
+
+        self.stop.store(false, Ordering::SeqCst);
+
+        let board = self.board.clone();
+        let output = self.output.clone();
+        let stop = Arc::clone(&self.stop);
+        let limits = SearchLimits {
+            history: self.history.clone(),
+            ..go.search_limits(board.side_to_move, Arc::clone(&stop))
+        };
+        let table = Arc::clone(&self.transposition_table);
+        let chess960 = self.chess960();
+
+        self.search_thread = Some(thread::spawn(move || {
+            let result = iterative_deepening(&board, &limits, &table, |result| {
+                _ = output.send(format_info(result, &board, chess960));
+            });
+
+            // "go infinite" may only send bestmove once the GUI sends stop.
+            while go.infinite && !stop.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            _ = output.send(format!(
+                "bestmove {}",
+                format_best_move(result.best_move, &board, chess960)
+            ));
+        }));
+    }
+
+    /*
+     * Print the divide output in the same "move: count" format Stockfish uses.
+     */
+    fn perft(&self, depth: u64) {
+        let start = Instant::now();
+        let (divide, nodes) = self.board.perft(depth);
+        let elapsed = start.elapsed();
+
+        for (uci_move, count) in divide {
+            self.send(format!("{uci_move}: {count}"));
+        }
+
+        self.send(String::new());
+        self.send(format!("Nodes searched: {nodes}"));
+        self.send(format!(
+            "info nodes {nodes} time {} nps {}",
+            elapsed.as_millis(),
+            nodes_per_second(nodes, elapsed)
+        ));
+    }
+
+    fn stop_search(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+
+        if let Some(search_thread) = self.search_thread.take() {
+            _ = search_thread.join();
+        }
+    }
+}
+
+pub fn format_info(result: &SearchResult, board: &ChessBoard, chess960: bool) -> String {
+    let score = match result.mate_in() {
+        Some(moves) => format!("mate {moves}"),
+        None => format!("cp {}", result.score),
+    };
+
+    let principal_variation = format_moves(board, &result.principal_variation, chess960);
+
+    format!(
+        "info depth {} score {score} nodes {} nps {} time {} pv {}",
+        result.depth,
+        result.nodes,
+        nodes_per_second(result.nodes, result.elapsed),
+        result.elapsed.as_millis(),
+        principal_variation.join(" ")
+    )
+}
+
+/* UCI uses the null move 0000 when there is no legal move to play. */
+pub fn format_best_move(
+    best_move: Option<LegalMove>,
+    board: &ChessBoard,
+    chess960: bool,
+) -> String {
+    match best_move {
+        Some(best_move) => board.uci_move_string(best_move, chess960),
+        None => String::from("0000"),
+    }
+}
+
+/*
+ * The UCI strings of a line of moves played from board. Chess960 castling depends on the position the castle is
+ * played in, so the line is played through.
+ */
+fn format_moves(board: &ChessBoard, moves: &[LegalMove], chess960: bool) -> Vec<String> {
+    let mut board = board.clone();
+
+    moves
+        .iter()
+        .map(|legal_move| {
+            let uci_move = board.uci_move_string(*legal_move, chess960);
+            board.make_move(*legal_move);
+            uci_move
+        })
+        .collect()
+}
+
+pub fn nodes_per_second(nodes: u64, elapsed: Duration) -> u64 {
+    let micros = elapsed.as_micros().max(1);
+
+    (nodes as u128 * 1_000_000 / micros) as u64
+}
+
+/*
+ * Run a UCI session on stdin and stdout until "quit" is received or stdin is closed.
+ */
+pub fn run_uci() {
+    let (output, receiver) = channel::<String>();
+
+    let printer = thread::spawn(move || {
+        for line in receiver {
+            println!("{line}");
+        }
+    });
+
+    let mut engine = UciEngine::new(output);
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        if !engine.handle_command(&line) {
+            break;
+        }
+    }
+
+    engine.stop_search();
+    drop(engine);
+    _ = printer.join();
+}